@@ -18,6 +18,7 @@ function Config( storage ) {
 	const projectDefaults = {
 		'title': '',
 		'editable': false,
+		'app/binaryScene': false,
 		'vr': false,
 		'renderer/antialias': true,
 		'renderer/shadows': true,
@@ -273,6 +273,9 @@ function Editor() {
 
 		/** Dispatched when a project asset file content changes (e.g. node editor save). Payload: normalized asset path. */
 		assetFileChanged: new Signal(),
+
+		/** Dispatched when files of the open project change outside the editor. Payload: [{ kind, path, from }] with paths relative to the project. */
+		projectFilesChanged: new Signal(),
 	};
 
 	this.storage = new _Storage();
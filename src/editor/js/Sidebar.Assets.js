@@ -910,6 +910,24 @@ function SidebarAssets( editor ) {
 		} );
 	}
 
+	// Files added, changed or removed outside the editor, e.g. exported from Blender
+	if ( editor.signals && editor.signals.projectFilesChanged ) {
+		editor.signals.projectFilesChanged.add( async ( changes ) => {
+			const assetChanges = changes.filter( ( change ) => change.path.startsWith( 'assets/' ) );
+			if ( assetChanges.length === 0 ) return;
+			try {
+				if ( typeof window.refreshAssets === 'function' ) await window.refreshAssets();
+			} catch ( err ) {
+				console.warn( '[Assets] Refresh on projectFilesChanged failed:', err );
+			}
+			for ( const change of assetChanges ) {
+				if ( change.kind === 'modified' ) {
+					editor.signals.assetFileChanged.dispatch( change.path.slice( 'assets/'.length ) );
+				}
+			}
+		} );
+	}
+
 	/**
 	 * Update preview cache and all img refs by path (when no MaterialAsset instance, e.g. after node editor save).
 	 */
@@ -33,6 +33,20 @@ function SidebarProjectApp( editor ) {
 
 	container.add( titleRow );
 
+	// Publishes the scene as app.cbor, which loads faster than app.json for
+	// scenes with large geometry buffers.
+	const binarySceneRow = new UIRow();
+	const binaryScene = new UICheckbox( config.getKey( 'project/app/binaryScene' ) ).setLeft( '100px' ).onChange( function () {
+
+		config.setKey( 'project/app/binaryScene', this.getValue() );
+
+	} );
+
+	binarySceneRow.add( new UIText( 'Binary scene' ).setClass( 'Label' ) );
+	binarySceneRow.add( binaryScene );
+
+	container.add( binarySceneRow );
+
 	const npmInstallButton = new UIButton( 'Install dependencies (npm)' );
 	npmInstallButton.setWidth( '160px' );
 	npmInstallButton.setMarginLeft( '90px' );
@@ -108,6 +122,9 @@ function SidebarProjectApp( editor ) {
 			loader.load( 'js/libs/app.js', function ( content ) {
 				toZip[ 'js/app.js' ] = strToU8( content );
 			} );
+			loader.load( 'js/libs/cbor.js', function ( content ) {
+				toZip[ 'js/cbor.js' ] = strToU8( content );
+			} );
 			loader.load( '../build/three.core.min.js', function ( content ) {
 				toZip[ 'js/three.core.js' ] = strToU8( content );
 			} );
@@ -121,8 +138,9 @@ function SidebarProjectApp( editor ) {
 		}
 
 		const buildFiles = {};
+		let sceneOutput = null;
 		let filesLoaded = 0;
-		const totalFiles = 6;
+		const totalFiles = 7;
 
 		function checkComplete() {
 			filesLoaded++;
@@ -134,6 +152,17 @@ function SidebarProjectApp( editor ) {
 		async function writeBuildFiles() {
 			try {
 				
+				// Copies the assets first: it fails, before anything is written,
+				// if a scene references an asset that doesn't exist.
+				await invoke( 'copy_assets_to_build', { projectPath: projectPath, checkReferences: true } );
+				
+				await invoke( 'write_build_scene', {
+					projectPath: projectPath,
+					filePath: 'app.json',
+					content: sceneOutput,
+					binary: config.getKey( 'project/app/binaryScene' ) === true
+				} );
+				
 				for ( const filePath in buildFiles ) {
 					const content = buildFiles[ filePath ];
 					const bytes = typeof content === 'string' 
@@ -147,8 +176,6 @@ function SidebarProjectApp( editor ) {
 					} );
 				}
 				
-				await invoke( 'copy_assets_to_build', { projectPath: projectPath } );
-				
 				alert( 'Build published successfully to project/build folder!' );
 			} catch ( error ) {
 				console.error( '[Publish] Failed to write build files:', error );
@@ -174,7 +201,7 @@ function SidebarProjectApp( editor ) {
 
 		output = JSON.stringify( output, null, '\t' );
 		output = output.replace( /[\n\t]+([\d\.e\-\[\]]+)/g, '$1' );
-		buildFiles[ 'app.json' ] = output;
+		sceneOutput = output;
 		checkComplete();
 
 		const title = config.getKey( 'project/title' );
@@ -201,6 +228,11 @@ function SidebarProjectApp( editor ) {
 			checkComplete();
 		} );
 		
+		loader.load( 'js/libs/cbor.js', function ( content ) {
+			buildFiles[ 'js/cbor.js' ] = content;
+			checkComplete();
+		} );
+		
 		
 		invoke( 'read_editor_template_file', { filePath: 'build/three.core.min.js' } )
 			.then( function ( content ) {
@@ -501,7 +501,6 @@ function SidebarScenes( editor ) {
 				}
 			},
 			scripts: {},
-			history: { undos: [], redos: [] },
 			environment: null
 		};
 
@@ -580,13 +579,12 @@ function SidebarScenes( editor ) {
 		storage.setCurrentScene( sceneName );
 		
 		try {
-			const invoke = window.__TAURI__.core.invoke;
-			const content = await invoke( 'read_scene_file', { 
-				projectPath: storage.getProjectPath(),
-				sceneName: sceneName
-			} );
+			const data = await storage.readScene( sceneName );
+			if ( !data ) {
+				alert( 'Load failed: ' + sceneName + ' is empty' );
+				return;
+			}
 			
-			const data = JSON.parse( content );
 			editor.clear();
 			editor.fromJSON( data );
 			
@@ -600,6 +598,8 @@ function SidebarScenes( editor ) {
 		}
 	}
 
+	storage.onSceneChangedOnDisk = loadScene;
+
 	signals.sceneGraphChanged.add( refresh );
 	signals.editorCleared.add( refresh );
 	signals.sceneLoaded.add( function ( sceneName ) {
@@ -608,6 +608,11 @@ function SidebarScenes( editor ) {
 		}
 		refresh();
 	} );
+	signals.projectFilesChanged.add( function ( changes ) {
+		if ( changes.some( ( change ) => change.path.startsWith( 'scenes/' ) ) ) {
+			refresh();
+		}
+	} );
 
 	refresh();
 
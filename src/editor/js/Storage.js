@@ -1,8 +1,20 @@
+import { decodeScene } from './libs/cbor.js';
+
 function Storage() {
 
 	let currentProjectPath = null;
 	let currentSceneName = 'Main.json';
 
+	// Revision of each scene as last read or saved, passed back on save so a
+	// scene changed on disk in between (e.g. by a git pull) isn't clobbered.
+	let sceneRevisions = {};
+
+	const CONFLICT_ERROR_PREFIX = 'CONFLICT: ';
+
+	function isConflict( error ) {
+		return String( error ).startsWith( CONFLICT_ERROR_PREFIX );
+	}
+
 	function isTauri() {
 		return typeof window !== 'undefined' && window.__TAURI__;
 	}
@@ -36,6 +48,91 @@ function Storage() {
 
 	restoreProjectPath();
 
+	// Reads a scene of the current project, or resolves with undefined if its
+	// file is empty. Projects storing scenes as CBOR get them as CBOR: large
+	// geometry buffers are much cheaper to pass that way than as a JSON string.
+	async function readScene( invoke, sceneName ) {
+		const projectConfig = JSON.parse( await invoke( 'read_project_config', { projectPath: currentProjectPath } ) );
+
+		if ( projectConfig.sceneEncoding === 'cbor' ) {
+			// The revision is read first: if the scene changes in between, the
+			// next save reports a conflict rather than overwriting the change.
+			sceneRevisions[ sceneName ] = await invoke( 'read_scene_revision', {
+				projectPath: currentProjectPath,
+				sceneName: sceneName
+			} );
+			const content = await invoke( 'read_scene_binary', {
+				projectPath: currentProjectPath,
+				sceneName: sceneName
+			} );
+			if ( !content || content.byteLength === 0 ) {
+				console.warn( 'Scene file is empty' );
+				return undefined;
+			}
+			return decodeScene( content );
+		}
+
+		const { content, revision } = await invoke( 'read_scene_file_with_revision', {
+			projectPath: currentProjectPath,
+			sceneName: sceneName
+		} );
+		sceneRevisions[ sceneName ] = revision;
+		if ( !content || content.trim() === '' ) {
+			console.warn( 'Scene file is empty' );
+			return undefined;
+		}
+		return JSON.parse( content );
+	}
+
+	// Saves a scene, checked against the revision it was read at. On a
+	// conflict the user picks between overwriting the scene on disk and
+	// reloading it, which drops the unsaved changes.
+	async function writeScene( invoke, sceneName, content ) {
+		const save = ( expectedRevision ) => invoke( 'write_scene_file', {
+			projectPath: currentProjectPath,
+			content: content,
+			sceneName: sceneName,
+			expectedRevision: expectedRevision
+		} );
+
+		try {
+			sceneRevisions[ sceneName ] = await save( sceneRevisions[ sceneName ] ?? null );
+		} catch ( error ) {
+			if ( !isConflict( error ) ) throw error;
+
+			const overwrite = confirm(
+				'Scene "' + sceneName.replace( '.json', '' ) + '" changed on disk since it was opened, e.g. by a git pull.\n\n' +
+				'Click OK to overwrite it with your changes, or Cancel to discard them and reload the scene from disk.'
+			);
+			if ( overwrite ) {
+				sceneRevisions[ sceneName ] = await save( null );
+			} else if ( storageImpl.onSceneChangedOnDisk ) {
+				await storageImpl.onSceneChangedOnDisk( sceneName );
+			}
+		}
+	}
+
+	// Applies `update` to project.json. It is read and written back right
+	// away, so on a conflict it is simply read again.
+	async function updateProjectConfig( invoke, update ) {
+		for ( let attempt = 0; ; attempt ++ ) {
+			const { content, revision } = await invoke( 'read_project_config_with_revision', { projectPath: currentProjectPath } );
+			const projectConfig = JSON.parse( content );
+			update( projectConfig );
+
+			try {
+				await invoke( 'write_project_config', {
+					projectPath: currentProjectPath,
+					content: JSON.stringify( projectConfig, null, '\t' ),
+					expectedRevision: revision
+				} );
+				return;
+			} catch ( error ) {
+				if ( !isConflict( error ) || attempt >= 2 ) throw error;
+			}
+		}
+	}
+
 	const storageImpl = {
 			init: function ( callback ) {
 				restoreProjectPath();
@@ -58,18 +155,7 @@ function Storage() {
 						return;
 					}
 
-					const content = await invoke( 'read_scene_file', { 
-						projectPath: currentProjectPath,
-						sceneName: currentSceneName
-					} );
-					
-					if ( !content || content.trim() === '' ) {
-						console.warn( 'Scene file is empty' );
-						callback( undefined );
-						return;
-					}
-					
-					const data = JSON.parse( content );
+					const data = await readScene( invoke, currentSceneName );
 					
 					if ( !data || !data.scene ) {
 						console.warn( 'Scene file does not contain valid scene data' );
@@ -166,11 +252,7 @@ function Storage() {
 					}
 
 					try {
-						await invoke( 'write_scene_file', { 
-							projectPath: currentProjectPath,
-							content: content,
-							sceneName: currentSceneName
-						} );
+						await writeScene( invoke, currentSceneName, content );
 					} catch (invokeError) {
 						console.error('[Storage] write_scene_file invoke error:', invokeError);
 						throw invokeError;
@@ -183,7 +265,11 @@ function Storage() {
 			},
 
 			clear: function () {},
+			// Called with a scene's name when the user chose to reload it after
+			// a conflicting save.
+			onSceneChangedOnDisk: null,
 			setProjectPath: function ( path ) {
+				if ( path !== currentProjectPath ) sceneRevisions = {};
 				currentProjectPath = path;
 				if (typeof window !== 'undefined' && window.sessionStorage) {
 					sessionStorage.setItem('editor_project_path', path);
@@ -198,6 +284,15 @@ function Storage() {
 			getCurrentScene: function () {
 				return currentSceneName;
 			},
+			readScene: async function ( sceneName ) {
+				restoreProjectPath();
+				if ( !currentProjectPath ) return undefined;
+				
+				const invoke = await tryGetTauriInvoke();
+				if ( !invoke ) return undefined;
+				
+				return readScene( invoke, sceneName );
+			},
 			listScenes: async function () {
 				restoreProjectPath();
 				if ( !currentProjectPath ) return [];
@@ -225,6 +320,7 @@ function Storage() {
 						projectPath: currentProjectPath,
 						sceneName: sceneName
 					} );
+					delete sceneRevisions[ sceneName ];
 					return true;
 				} catch ( error ) {
 					console.error( '[Storage] Failed to delete scene:', error );
@@ -248,6 +344,8 @@ function Storage() {
 					if ( currentSceneName === oldName ) {
 						currentSceneName = newName;
 					}
+					sceneRevisions[ newName ] = sceneRevisions[ oldName ];
+					delete sceneRevisions[ oldName ];
 					
 					return true;
 				} catch ( error ) {
@@ -263,18 +361,12 @@ function Storage() {
 					const invoke = await tryGetTauriInvoke();
 					if ( !invoke ) return false;
 					
-					const content = await invoke( 'read_project_config', { projectPath: currentProjectPath } );
-					const projectConfig = JSON.parse( content );
-					
-					if ( !projectConfig.scenes ) {
-						projectConfig.scenes = {};
-					}
-					
-					projectConfig.scenes[ sceneName ] = config;
-					
-					await invoke( 'write_project_config', { 
-						projectPath: currentProjectPath,
-						content: JSON.stringify( projectConfig, null, '\t' )
+					await updateProjectConfig( invoke, ( projectConfig ) => {
+						if ( !projectConfig.scenes ) {
+							projectConfig.scenes = {};
+						}
+						
+						projectConfig.scenes[ sceneName ] = config;
 					} );
 					
 					return true;
@@ -291,14 +383,8 @@ function Storage() {
 					const invoke = await tryGetTauriInvoke();
 					if ( !invoke ) return false;
 					
-					const content = await invoke( 'read_project_config', { projectPath: currentProjectPath } );
-					const projectConfig = JSON.parse( content );
-					
-					projectConfig.defaultScene = sceneName;
-					
-					await invoke( 'write_project_config', { 
-						projectPath: currentProjectPath,
-						content: JSON.stringify( projectConfig, null, '\t' )
+					await updateProjectConfig( invoke, ( projectConfig ) => {
+						projectConfig.defaultScene = sceneName;
 					} );
 					
 					return true;
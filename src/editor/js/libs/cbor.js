@@ -0,0 +1,175 @@
+/**
+ * Decoder for binary (CBOR) scenes, as written by the backend's scene codec
+ * (`scene_codec.rs`). Gives the same document as parsing the scene's JSON:
+ * typed arrays (RFC 8746) come back as plain arrays of numbers.
+ */
+
+// Typed array tags (little endian), by element size and reader.
+const TYPED_ARRAYS = {
+	64: [ 1, ( view, offset ) => view.getUint8( offset ) ],
+	68: [ 1, ( view, offset ) => view.getUint8( offset ) ],
+	69: [ 2, ( view, offset ) => view.getUint16( offset, true ) ],
+	70: [ 4, ( view, offset ) => view.getUint32( offset, true ) ],
+	72: [ 1, ( view, offset ) => view.getInt8( offset ) ],
+	77: [ 2, ( view, offset ) => view.getInt16( offset, true ) ],
+	78: [ 4, ( view, offset ) => view.getInt32( offset, true ) ],
+	85: [ 4, ( view, offset ) => view.getFloat32( offset, true ) ],
+	86: [ 8, ( view, offset ) => view.getFloat64( offset, true ) ]
+};
+
+const textDecoder = new TextDecoder( 'utf-8', { fatal: true } );
+
+function decodeScene( buffer ) {
+
+	const bytes = buffer instanceof Uint8Array ? buffer : new Uint8Array( buffer );
+	const view = new DataView( bytes.buffer, bytes.byteOffset, bytes.byteLength );
+	let offset = 0;
+
+	function fail( message ) {
+
+		throw new Error( 'Invalid binary scene: ' + message );
+
+	}
+
+	function need( length ) {
+
+		if ( offset + length > bytes.length ) fail( 'unexpected end of data' );
+
+	}
+
+	function readArgument( info ) {
+
+		if ( info < 24 ) return info;
+
+		switch ( info ) {
+
+			case 24: need( 1 ); return view.getUint8( offset ++ );
+			case 25: need( 2 ); offset += 2; return view.getUint16( offset - 2 );
+			case 26: need( 4 ); offset += 4; return view.getUint32( offset - 4 );
+			case 27: need( 8 ); offset += 8; return Number( view.getBigUint64( offset - 8 ) );
+
+		}
+
+		fail( 'unsupported length encoding' );
+
+	}
+
+	function readHalf( half ) {
+
+		const exponent = ( half >> 10 ) & 0x1f;
+		const fraction = half & 0x3ff;
+		const sign = half & 0x8000 ? - 1 : 1;
+
+		if ( exponent === 0 ) return sign * fraction * Math.pow( 2, - 24 );
+		if ( exponent === 0x1f ) return fraction ? NaN : sign * Infinity;
+		return sign * ( 1 + fraction / 1024 ) * Math.pow( 2, exponent - 15 );
+
+	}
+
+	function readBytes( length ) {
+
+		need( length );
+		offset += length;
+		return bytes.subarray( offset - length, offset );
+
+	}
+
+	function readTypedArray( tag, data ) {
+
+		const [ size, read ] = TYPED_ARRAYS[ tag ];
+		if ( data.length % size !== 0 ) fail( 'truncated typed array' );
+
+		const dataView = new DataView( data.buffer, data.byteOffset, data.byteLength );
+		const items = new Array( data.length / size );
+		for ( let i = 0; i < items.length; i ++ ) {
+
+			items[ i ] = read( dataView, i * size );
+
+		}
+
+		return items;
+
+	}
+
+	function readValue() {
+
+		need( 1 );
+		const initial = view.getUint8( offset ++ );
+		const major = initial >> 5;
+		const info = initial & 0x1f;
+
+		if ( major === 7 ) {
+
+			switch ( info ) {
+
+				case 20: return false;
+				case 21: return true;
+				case 22: return null;
+				case 23: return null;
+				case 25: need( 2 ); offset += 2; return readHalf( view.getUint16( offset - 2 ) );
+				case 26: need( 4 ); offset += 4; return view.getFloat32( offset - 4 );
+				case 27: need( 8 ); offset += 8; return view.getFloat64( offset - 8 );
+
+			}
+
+			fail( 'unsupported value' );
+
+		}
+
+		const argument = readArgument( info );
+
+		switch ( major ) {
+
+			case 0: return argument;
+			case 1: return - 1 - argument;
+			case 2: return Array.from( readBytes( argument ) );
+			case 3: return textDecoder.decode( readBytes( argument ) );
+
+			case 4: {
+
+				const items = new Array( argument );
+				for ( let i = 0; i < argument; i ++ ) items[ i ] = readValue();
+				return items;
+
+			}
+
+			case 5: {
+
+				const object = {};
+				for ( let i = 0; i < argument; i ++ ) {
+
+					const key = readValue();
+					if ( typeof key !== 'string' ) fail( 'map key is not a string' );
+					// Like JSON.parse, `__proto__` is an ordinary key.
+					Object.defineProperty( object, key, { value: readValue(), enumerable: true, writable: true, configurable: true } );
+
+				}
+
+				return object;
+
+			}
+
+			case 6: {
+
+				if ( TYPED_ARRAYS[ argument ] !== undefined && offset < bytes.length && view.getUint8( offset ) >> 5 === 2 ) {
+
+					const data = readBytes( readArgument( view.getUint8( offset ++ ) & 0x1f ) );
+					return readTypedArray( argument, data );
+
+				}
+
+				return readValue();
+
+			}
+
+		}
+
+	}
+
+	const value = readValue();
+	if ( offset !== bytes.length ) fail( 'trailing data' );
+	return value;
+
+}
+
+export { decodeScene };
@@ -218,6 +218,7 @@ function renderProjectsTable(table, tbody) {
 
 function setupEventListeners() {
 	document.getElementById('new-project-btn').addEventListener('click', showNewProjectModal);
+	document.getElementById('trash-btn').addEventListener('click', showTrashModal);
 	document.getElementById('modal-cancel').addEventListener('click', hideNewProjectModal);
 	document.getElementById('modal-create').addEventListener('click', createProject);
 	document.getElementById('new-project-form').addEventListener('submit', (e) => {
@@ -327,11 +328,54 @@ async function createProject() {
 	}
 }
 
+const LOCKED_ERROR_PREFIX = 'PROJECT_LOCKED: ';
+
+// Returns who holds the project's lock if `error` is a PROJECT_LOCKED error.
+function parseLockedError(error) {
+	const text = String(error);
+	if (!text.startsWith(LOCKED_ERROR_PREFIX)) {
+		return null;
+	}
+	try {
+		return JSON.parse(text.slice(LOCKED_ERROR_PREFIX.length));
+	} catch {
+		return {};
+	}
+}
+
+function describeLockHolder(holder) {
+	const host = holder.hostname ? 'on ' + holder.hostname : 'elsewhere';
+	const since = holder.acquiredAt ? ' (opened ' + formatRelativeDate(holder.acquiredAt).toLowerCase() + ')' : '';
+	return 'This project is open in another editor ' + host + since + '.';
+}
+
 async function openProject(path) {
 	try {
 		await invoke('open_project', { path });
 	} catch (error) {
-		showError('Failed to open project: ' + error);
+		const holder = parseLockedError(error);
+		if (!holder) {
+			showError('Failed to open project: ' + error);
+			return;
+		}
+
+		const mode = await showChoiceModal(
+			'Project In Use',
+			describeLockHolder(holder) + ' Open it read-only, or take it over if that editor is no longer running; changes made there may be lost.',
+			[
+				{ text: 'Open Read-Only', value: 'read_only', className: 'dialog-btn-ok' },
+				{ text: 'Take Over', value: 'take_over' }
+			]
+		);
+		if (!mode) {
+			return;
+		}
+
+		try {
+			await invoke('open_project', { path, mode });
+		} catch (error) {
+			showError('Failed to open project: ' + error);
+		}
 	}
 }
 
@@ -341,16 +385,51 @@ async function deleteProject(path) {
 		return;
 	}
 
+	const cleanPath = String(path).trim();
 	try {
-		const cleanPath = String(path).trim();
 		await invoke('delete_project', { path: cleanPath });
 		await loadProjects();
 	} catch (error) {
-		showError('Failed to delete project: ' + error);
+		const holder = parseLockedError(error);
+		if (!holder) {
+			showError('Failed to delete project: ' + error);
+			return;
+		}
+
+		const breakLock = await showConfirmModal(
+			'Project In Use',
+			describeLockHolder(holder) + ' Break its lock only if that editor is no longer running.',
+			'Break Lock'
+		);
+		if (!breakLock) {
+			return;
+		}
+
+		try {
+			await invoke('break_project_lock', { path: cleanPath });
+			await invoke('delete_project', { path: cleanPath });
+			await loadProjects();
+		} catch (error) {
+			showError('Failed to delete project: ' + error);
+		}
 	}
 }
 
 function showDeleteConfirmModal() {
+	return showConfirmModal(
+		'Delete Project',
+		'Are you sure you want to delete this project? It is moved to the trash, from where it can be restored until the trash is emptied.',
+		'Delete'
+	);
+}
+
+function showConfirmModal(titleText, messageText, confirmText) {
+	return showChoiceModal(titleText, messageText, [{ text: confirmText, value: true }])
+		.then(value => value === true);
+}
+
+// Resolves with the `value` of the chosen button, or null if cancelled.
+function showChoiceModal(titleText, messageText, choices) {
 	return new Promise((resolve) => {
 		const modal = document.createElement('div');
 		modal.className = 'modal-overlay';
@@ -360,11 +439,11 @@ function showDeleteConfirmModal() {
 		
 		const title = document.createElement('div');
 		title.className = 'dialog-title';
-		title.textContent = 'Delete Project';
+		title.textContent = titleText;
 		
 		const message = document.createElement('div');
 		message.className = 'dialog-message';
-		message.textContent = 'Are you sure you want to delete this project? This action cannot be undone.';
+		message.textContent = messageText;
 		
 		const buttons = document.createElement('div');
 		buttons.className = 'dialog-buttons';
@@ -374,19 +453,22 @@ function showDeleteConfirmModal() {
 		cancelBtn.textContent = 'Cancel';
 		cancelBtn.onclick = () => {
 			document.body.removeChild(modal);
-			resolve(false);
+			document.removeEventListener('keydown', escapeHandler);
+			resolve(null);
 		};
-		
-		const deleteBtn = document.createElement('button');
-		deleteBtn.className = 'dialog-btn dialog-btn-delete';
-		deleteBtn.textContent = 'Delete';
-		deleteBtn.onclick = () => {
-			document.body.removeChild(modal);
-			resolve(true);
-		};
-		
 		buttons.appendChild(cancelBtn);
-		buttons.appendChild(deleteBtn);
+		
+		choices.forEach(choice => {
+			const choiceBtn = document.createElement('button');
+			choiceBtn.className = 'dialog-btn ' + (choice.className || 'dialog-btn-delete');
+			choiceBtn.textContent = choice.text;
+			choiceBtn.onclick = () => {
+				document.body.removeChild(modal);
+				document.removeEventListener('keydown', escapeHandler);
+				resolve(choice.value);
+			};
+			buttons.appendChild(choiceBtn);
+		});
 		
 		dialog.appendChild(title);
 		dialog.appendChild(message);
@@ -398,13 +480,126 @@ function showDeleteConfirmModal() {
 			if (e.key === 'Escape') {
 				document.body.removeChild(modal);
 				document.removeEventListener('keydown', escapeHandler);
-				resolve(false);
+				resolve(null);
 			}
 		};
 		document.addEventListener('keydown', escapeHandler);
 	});
 }
 
+async function showTrashModal() {
+	let trashed;
+	try {
+		trashed = await invoke('list_trashed_projects');
+	} catch (error) {
+		showError('Failed to load trash: ' + error);
+		return;
+	}
+
+	const modal = document.createElement('div');
+	modal.className = 'modal-overlay';
+	
+	const dialog = document.createElement('div');
+	dialog.className = 'dialog dialog-trash';
+	
+	const title = document.createElement('div');
+	title.className = 'dialog-title';
+	title.textContent = 'Trash';
+	dialog.appendChild(title);
+	
+	const close = () => {
+		if (modal.parentNode) {
+			document.body.removeChild(modal);
+		}
+		document.removeEventListener('keydown', escapeHandler);
+	};
+	
+	const list = document.createElement('div');
+	list.className = 'trash-list';
+	if (trashed.length === 0) {
+		list.className = 'dialog-message';
+		list.textContent = 'The trash is empty.';
+	}
+	
+	trashed.forEach(project => {
+		const item = document.createElement('div');
+		item.className = 'trash-item';
+		
+		const info = document.createElement('div');
+		info.className = 'trash-item-info';
+		const name = document.createElement('div');
+		name.className = 'trash-item-name';
+		name.textContent = project.name;
+		const details = document.createElement('div');
+		details.className = 'trash-item-details';
+		details.textContent = 'Deleted ' + formatRelativeDate(project.deleted_at).toLowerCase() + ' from ' + truncatePath(project.original_path);
+		details.title = project.original_path;
+		info.appendChild(name);
+		info.appendChild(details);
+		
+		const restoreBtn = document.createElement('button');
+		restoreBtn.className = 'dialog-btn dialog-btn-cancel';
+		restoreBtn.textContent = 'Restore';
+		restoreBtn.onclick = async () => {
+			try {
+				await invoke('restore_project', { id: project.id });
+				close();
+				await loadProjects();
+			} catch (error) {
+				showError('Failed to restore project: ' + error);
+			}
+		};
+		
+		item.appendChild(info);
+		item.appendChild(restoreBtn);
+		list.appendChild(item);
+	});
+	dialog.appendChild(list);
+	
+	const buttons = document.createElement('div');
+	buttons.className = 'dialog-buttons';
+	
+	const closeBtn = document.createElement('button');
+	closeBtn.className = 'dialog-btn dialog-btn-cancel';
+	closeBtn.textContent = 'Close';
+	closeBtn.onclick = close;
+	
+	const emptyBtn = document.createElement('button');
+	emptyBtn.className = 'dialog-btn dialog-btn-delete';
+	emptyBtn.textContent = 'Empty Trash';
+	emptyBtn.disabled = trashed.length === 0;
+	emptyBtn.onclick = async () => {
+		close();
+		const confirmed = await showConfirmModal(
+			'Empty Trash',
+			'Permanently delete the ' + trashed.length + ' project(s) in the trash? This action cannot be undone.',
+			'Empty Trash'
+		);
+		if (!confirmed) {
+			return;
+		}
+		try {
+			await invoke('purge_trash');
+		} catch (error) {
+			showError('Failed to empty trash: ' + error);
+		}
+	};
+	
+	buttons.appendChild(closeBtn);
+	buttons.appendChild(emptyBtn);
+	dialog.appendChild(buttons);
+	
+	modal.appendChild(dialog);
+	document.body.appendChild(modal);
+	
+	const escapeHandler = (e) => {
+		if (e.key === 'Escape') {
+			close();
+		}
+	};
+	document.addEventListener('keydown', escapeHandler);
+}
+
 let currentMenu = null;
 
 function showProjectMenu(path, event) {
@@ -0,0 +1,441 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::project_check::{read_assets_tree, ASSETS_FILE};
+use crate::project_metadata_store::ProjectMetadataStore;
+use crate::scene::SceneDocument;
+use crate::scene_store;
+
+/// Dangling references listed in a build gate error before the rest are
+/// summarized.
+const MAX_LISTED_IN_ERROR: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    /// `url` of an entry in the images table.
+    Image,
+    /// `userData.assetPath` of a texture.
+    Texture,
+    /// `userData.assetPath` of a material.
+    Material,
+    /// `userData.assetPath` or `userData.modelPath` of an object.
+    Model,
+    /// `assetPath` of a script in an object's `userData.scripts`.
+    Script,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DanglingReason {
+    /// Neither a file in `assets/` nor an entry of assets.json.
+    NotFound,
+    /// Listed in assets.json, but the file (or the model it comes from) is
+    /// gone.
+    MissingFile,
+    /// On another drive or leading out of `assets/` with `..`. Builds only
+    /// ship `assets/`, so such a file is missing there even if it exists.
+    OutsideAssets,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DanglingReference {
+    pub kind: ReferenceKind,
+    /// The reference as written in the scene.
+    pub reference: String,
+    /// What it resolves to, relative to `assets/`.
+    pub asset_path: String,
+    pub reason: DanglingReason,
+    /// The image, texture or material holding the reference.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Hierarchy paths of the objects affected, e.g. `Scene/Forest/Tree`.
+    pub objects: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SceneReferences {
+    pub scene_name: String,
+    /// Asset references found in the scene, dangling or not.
+    pub references: usize,
+    pub dangling: Vec<DanglingReference>,
+    /// Set if the scene could not be read; nothing else is reported for it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct AssetReferenceReport {
+    pub scenes: Vec<SceneReferences>,
+}
+
+/// A reference found in a scene, before it is resolved.
+struct Reference {
+    kind: ReferenceKind,
+    reference: String,
+    uuid: Option<String>,
+    objects: Vec<String>,
+}
+
+/// Entries of assets.json by path relative to `assets/`, with the model file
+/// for virtual entries inside a model.
+type AssetCatalog = HashMap<String, Option<String>>;
+
+/// Checks the asset references of every scene of a project against
+/// `assets/` and assets.json.
+pub fn asset_reference_report(project_path: &Path) -> Result<AssetReferenceReport, String> {
+    let assets_dir = project_path.join("assets");
+    let mut catalog = AssetCatalog::new();
+    if let Some(tree) = read_assets_tree(&assets_dir.join(ASSETS_FILE))? {
+        collect_catalog(&tree, &mut catalog);
+    }
+
+    let mut report = AssetReferenceReport::default();
+    for scene_name in scene_store::list_scene_names(project_path) {
+        let document = scene_store::read_scene(project_path, &scene_name)
+            .and_then(|content| SceneDocument::from_json_str(&content));
+
+        let scene = match document {
+            Ok(document) => {
+                let references = collect_references(&document);
+                SceneReferences {
+                    scene_name,
+                    references: references.len(),
+                    dangling: references.into_iter()
+                        .filter_map(|reference| resolve(reference, &assets_dir, &catalog))
+                        .collect(),
+                    error: None,
+                }
+            }
+            Err(error) => SceneReferences {
+                scene_name,
+                references: 0,
+                dangling: Vec::new(),
+                error: Some(error),
+            },
+        };
+        report.scenes.push(scene);
+    }
+
+    Ok(report)
+}
+
+/// Fails if a scene included in the build has dangling asset references or
+/// can't be read. The error lists them, one per line.
+pub fn ensure_build_references(metadata_store: &ProjectMetadataStore, project_path: &Path) -> Result<(), String> {
+    let metadata = metadata_store.load_optional(project_path)?;
+    let report = asset_reference_report(project_path)?;
+
+    let mut lines = Vec::new();
+    for scene in &report.scenes {
+        let in_build = metadata.as_ref()
+            .map_or(true, |m| m.scene_config(&scene.scene_name).include_in_build);
+        if !in_build {
+            continue;
+        }
+
+        if let Some(error) = &scene.error {
+            lines.push(format!("{}: {}", scene.scene_name, error));
+        }
+        for dangling in &scene.dangling {
+            let reason = match dangling.reason {
+                DanglingReason::NotFound => "not found",
+                DanglingReason::MissingFile => "file missing",
+                DanglingReason::OutsideAssets => "outside assets/",
+            };
+            let user = dangling.objects.first()
+                .map(|path| format!(" ({})", path))
+                .unwrap_or_default();
+            lines.push(format!("{}: {}{}: {}", scene.scene_name, dangling.reference, user, reason));
+        }
+    }
+
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    let total = lines.len();
+    if total > MAX_LISTED_IN_ERROR {
+        lines.truncate(MAX_LISTED_IN_ERROR);
+        lines.push(format!("... and {} more", total - MAX_LISTED_IN_ERROR));
+    }
+    Err(format!("Build has {} broken asset reference(s):\n{}", total, lines.join("\n")))
+}
+
+fn collect_catalog(folder: &Value, catalog: &mut AssetCatalog) {
+    let text = |value: &Value, key: &str| -> Option<String> {
+        value.get(key)?.as_str().map(normalize).filter(|p| !p.is_empty())
+    };
+
+    for file in folder.get("files").and_then(Value::as_array).into_iter().flatten() {
+        if let Some(path) = text(file, "path") {
+            catalog.insert(path, text(file, "modelPath"));
+        }
+    }
+
+    for child in folder.get("children").and_then(Value::as_array).into_iter().flatten() {
+        collect_catalog(child, catalog);
+    }
+}
+
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches('/').to_string()
+}
+
+/// The path a reference points to relative to `assets/`, the way the
+/// editor's loader resolves it. Embedded and remote URLs are not asset
+/// references.
+fn asset_path(reference: &str) -> Option<String> {
+    let reference = reference.trim();
+    if reference.is_empty()
+        || ["data:", "blob:", "http:", "https:"].iter().any(|scheme| reference.starts_with(scheme))
+    {
+        return None;
+    }
+
+    let mut path = normalize(reference);
+    if let Some(index) = path.find("/assets/") {
+        path = path[index + "/assets/".len()..].to_string();
+    }
+    while let Some(rest) = path.strip_prefix("assets/") {
+        path = rest.to_string();
+    }
+    Some(path)
+}
+
+/// Whether an asset path stays inside `assets/`: without a drive and
+/// without `..`. A leading `/` already stands for `assets/` itself.
+fn is_inside_assets(path: &str) -> bool {
+    let mut segments = path.split('/');
+    !segments.next().is_some_and(|first| first.contains(':') || first == "..")
+        && !segments.any(|segment| segment == "..")
+}
+
+/// `None` if the reference resolves, otherwise why it doesn't.
+fn resolve(reference: Reference, assets_dir: &Path, catalog: &AssetCatalog) -> Option<DanglingReference> {
+    let asset_path = asset_path(&reference.reference)?;
+
+    let reason = match catalog.get(&asset_path) {
+        _ if !is_inside_assets(&asset_path) => DanglingReason::OutsideAssets,
+        _ if assets_dir.join(&asset_path).is_file() => return None,
+        Some(Some(model_path)) if is_inside_assets(model_path) && assets_dir.join(model_path).is_file() => return None,
+        Some(_) => DanglingReason::MissingFile,
+        None => DanglingReason::NotFound,
+    };
+
+    Some(DanglingReference {
+        kind: reference.kind,
+        reference: reference.reference,
+        asset_path,
+        reason,
+        uuid: reference.uuid,
+        objects: reference.objects,
+    })
+}
+
+/// Every asset reference of a scene's tree, with the objects it affects.
+/// Only references that look like asset paths are kept.
+fn collect_references(document: &SceneDocument) -> Vec<Reference> {
+    let scene = &document.scene;
+    let texture_uuids: HashSet<&str> = scene.textures.iter().map(|t| t.uuid.as_str()).collect();
+
+    // Who uses what: object paths by material and by texture used directly
+    // (e.g. a scene background).
+    let mut material_users: HashMap<String, Vec<String>> = HashMap::new();
+    let mut texture_users: HashMap<String, Vec<String>> = HashMap::new();
+    let mut references = Vec::new();
+
+    for (object, path) in scene.object.with_paths() {
+        for uuid in object.material_uuids() {
+            material_users.entry(uuid.clone()).or_default().push(path.clone());
+        }
+
+        let mut strings = HashSet::new();
+        for (key, value) in &object.extra {
+            if key != "userData" {
+                collect_strings(value, &mut strings);
+            }
+        }
+        for uuid in strings.into_iter().filter(|s| texture_uuids.contains(s)) {
+            texture_users.entry(uuid.to_string()).or_default().push(path.clone());
+        }
+
+        let user_data = object.extra.get("userData");
+        for key in ["assetPath", "modelPath"] {
+            if let Some(reference) = user_data.and_then(|u| u.get(key)).and_then(Value::as_str) {
+                references.push(Reference {
+                    kind: ReferenceKind::Model,
+                    reference: reference.to_string(),
+                    uuid: None,
+                    objects: vec![path.clone()],
+                });
+            }
+        }
+
+        let scripts = user_data.and_then(|u| u.get("scripts")).and_then(Value::as_array);
+        for script in scripts.into_iter().flatten() {
+            if let Some(reference) = script.get("assetPath").and_then(Value::as_str) {
+                references.push(Reference {
+                    kind: ReferenceKind::Script,
+                    reference: reference.to_string(),
+                    uuid: None,
+                    objects: vec![path.clone()],
+                });
+            }
+        }
+    }
+
+    // Materials pass their users on to the textures they reference.
+    for material in &scene.materials {
+        let users = material_users.get(&material.uuid).cloned().unwrap_or_default();
+        let mut strings = HashSet::new();
+        for value in material.extra.values() {
+            collect_strings(value, &mut strings);
+        }
+        for uuid in strings.into_iter().filter(|s| texture_uuids.contains(s)) {
+            texture_users.entry(uuid.to_string()).or_default().extend(users.iter().cloned());
+        }
+
+        if let Some(reference) = asset_path_of(&material.extra) {
+            references.push(Reference {
+                kind: ReferenceKind::Material,
+                reference,
+                uuid: Some(material.uuid.clone()),
+                objects: users,
+            });
+        }
+    }
+
+    let mut image_users: HashMap<String, Vec<String>> = HashMap::new();
+    for texture in &scene.textures {
+        let users = texture_users.get(&texture.uuid).cloned().unwrap_or_default();
+        if let Some(image) = texture.extra.get("image").and_then(Value::as_str) {
+            image_users.entry(image.to_string()).or_default().extend(users.iter().cloned());
+        }
+
+        if let Some(reference) = asset_path_of(&texture.extra) {
+            references.push(Reference {
+                kind: ReferenceKind::Texture,
+                reference,
+                uuid: Some(texture.uuid.clone()),
+                objects: users,
+            });
+        }
+    }
+
+    for image in &scene.images {
+        let urls = match image.extra.get("url") {
+            Some(Value::String(url)) => vec![url.as_str()],
+            Some(Value::Array(urls)) => urls.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        for url in urls {
+            references.push(Reference {
+                kind: ReferenceKind::Image,
+                reference: url.to_string(),
+                uuid: Some(image.uuid.clone()),
+                objects: image_users.get(&image.uuid).cloned().unwrap_or_default(),
+            });
+        }
+    }
+
+    for reference in &mut references {
+        reference.objects.sort();
+        reference.objects.dedup();
+    }
+    references.retain(|reference| asset_path(&reference.reference).is_some());
+    references
+}
+
+fn collect_strings<'a>(value: &'a Value, out: &mut HashSet<&'a str>) {
+    match value {
+        Value::String(s) => {
+            out.insert(s);
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_strings(item, out)),
+        Value::Object(map) => map.values().for_each(|item| collect_strings(item, out)),
+        _ => {}
+    }
+}
+
+fn asset_path_of(extra: &Map<String, Value>) -> Option<String> {
+    extra.get("userData")?.get("assetPath")?.as_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn maps_references_into_assets() {
+        assert_eq!(asset_path("assets/models/tree.glb").as_deref(), Some("models/tree.glb"));
+        assert_eq!(asset_path("/projects/demo/assets/models/tree.glb").as_deref(), Some("models/tree.glb"));
+        assert_eq!(asset_path("/models/tree.glb").as_deref(), Some("models/tree.glb"));
+        assert_eq!(asset_path("models\\tree.glb").as_deref(), Some("models/tree.glb"));
+        assert_eq!(asset_path("data:image/png;base64,AAAA"), None);
+        assert_eq!(asset_path("https://example.com/tree.glb"), None);
+        assert_eq!(asset_path("  "), None);
+    }
+
+    #[test]
+    fn keeps_references_inside_assets() {
+        assert!(is_inside_assets("models/tree.glb"));
+        assert!(is_inside_assets("models/..tree.glb"));
+        assert!(!is_inside_assets("../secret.png"));
+        assert!(!is_inside_assets("models/../../secret.png"));
+        assert!(!is_inside_assets(&asset_path("/../secret.png").unwrap()));
+        assert!(!is_inside_assets("C:/secret.png"));
+    }
+
+    #[test]
+    fn reports_dangling_references() {
+        let project = std::env::temp_dir()
+            .join(format!("asset-references-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&project);
+        fs::create_dir_all(project.join("assets/models")).unwrap();
+        fs::create_dir_all(project.join("scenes")).unwrap();
+        fs::write(project.join("assets/models/tree.glb"), b"glb").unwrap();
+        fs::write(project.join("secret.png"), b"png").unwrap();
+        fs::write(project.join("assets").join(ASSETS_FILE), r#"{
+            "files": [
+                { "path": "models/tree.glb/Leaves", "modelPath": "models/tree.glb" },
+                { "path": "textures/gone.png" }
+            ]
+        }"#).unwrap();
+        fs::write(project.join("scenes/main.json"), r#"{
+            "camera": { "object": { "type": "PerspectiveCamera" } },
+            "scene": {
+                "object": { "type": "Scene", "name": "Level", "children": [
+                    { "type": "Mesh", "name": "Tree", "userData": { "assetPath": "assets/models/tree.glb" } },
+                    { "type": "Mesh", "name": "Leaves", "userData": { "modelPath": "models/tree.glb/Leaves" } },
+                    { "type": "Mesh", "name": "Rock", "userData": { "assetPath": "models/rock.glb" } }
+                ] },
+                "images": [
+                    { "uuid": "i1", "url": "textures/gone.png" },
+                    { "uuid": "i2", "url": "../secret.png" },
+                    { "uuid": "i3", "url": "data:image/png;base64,AAAA" }
+                ]
+            }
+        }"#).unwrap();
+
+        let report = asset_reference_report(&project).unwrap();
+        fs::remove_dir_all(&project).unwrap();
+
+        let scene = &report.scenes[0];
+        assert_eq!(scene.scene_name, "main.json");
+        assert_eq!(scene.error, None);
+        assert_eq!(scene.references, 5);
+        let dangling: Vec<_> = scene.dangling.iter()
+            .map(|d| (d.reference.as_str(), d.reason))
+            .collect();
+        assert_eq!(dangling, vec![
+            ("models/rock.glb", DanglingReason::NotFound),
+            ("textures/gone.png", DanglingReason::MissingFile),
+            ("../secret.png", DanglingReason::OutsideAssets),
+        ]);
+        assert_eq!(scene.dangling[0].objects, vec!["Level/Rock".to_string()]);
+    }
+}
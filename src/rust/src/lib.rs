@@ -3,9 +3,39 @@
 
 mod websocket;
 mod project_manager;
+mod project_templates;
+mod project_archive;
+mod project_metadata;
+mod project_metadata_store;
+mod project_locations;
+mod project_lock;
+mod project_check;
+mod scene;
+mod scene_store;
+mod scene_lock;
+mod scene_codec;
+mod scene_recovery;
+mod scene_history;
+mod scene_diff;
+mod scene_merge;
+mod scene_stats;
+mod scene_query;
+mod asset_references;
+mod project_watcher;
+mod revision;
+mod undo_history;
+mod atomic_write;
 
 use websocket::{WebSocketServer, EditorMessage, EngineMessage};
 use project_manager::ProjectManager;
+use project_templates::TemplateRegistry;
+use project_metadata::ProjectMetadata;
+use project_metadata_store::ProjectMetadataStore;
+use project_locations::{LocationKind, ProjectLocations};
+use project_lock::{OpenMode, ProjectLock};
+use scene_lock::SceneLocks;
+use scene::SceneDocument;
+use atomic_write::write_atomic;
 use parking_lot::Mutex;
 use std::sync::Arc;
 use std::process::{Command, Child};
@@ -19,6 +49,38 @@ struct AppState {
     playing: Arc<Mutex<bool>>,
     entities: Arc<Mutex<Vec<serde_json::Value>>>,
     engine_process: Arc<Mutex<Option<Child>>>,
+    project_session: Arc<Mutex<Option<ProjectSession>>>,
+    /// All access to project.json goes through here.
+    project_metadata: Arc<ProjectMetadataStore>,
+    /// Held while a command replaces or removes a scene.
+    scene_locks: Arc<SceneLocks>,
+}
+
+/// The project currently open in the editor window.
+struct ProjectSession {
+    path: String,
+    read_only: bool,
+    /// Released when the session is dropped.
+    _lock: Option<ProjectLock>,
+    /// Reports external changes to the editor; stops with the session.
+    _watcher: Option<project_watcher::ProjectWatcher>,
+}
+
+#[derive(serde::Serialize)]
+struct ProjectSessionInfo {
+    path: String,
+    read_only: bool,
+}
+
+/// Rejects a call that would modify the project at `project_path` if this
+/// editor has it open in read-only mode, or if another editor holds its
+/// lock. Every command that modifies project files must call this first.
+fn ensure_writable(state: &AppState, project_path: &str) -> Result<(), String> {
+    let session = state.project_session.lock();
+    let read_only_project = session.as_ref()
+        .filter(|session| session.read_only)
+        .map(|session| std::path::Path::new(&session.path));
+    project_lock::ensure_writable(read_only_project, std::path::Path::new(project_path))
 }
 
 #[tauri::command]
@@ -40,104 +102,249 @@ fn get_entities(state: State<AppState>) -> Vec<serde_json::Value> {
     state.entities.lock().clone()
 }
 
+fn editor_config_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let config_dir = app.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    Ok(config_dir.join("editor.json"))
+}
+
+fn project_manager(app: &tauri::AppHandle) -> Result<ProjectManager, String> {
+    let locations = ProjectLocations::load(&editor_config_path(app)?)?;
+    Ok(ProjectManager::new()?
+        .with_locations(locations)
+        .with_metadata_store(app.state::<AppState>().project_metadata.clone()))
+}
+
+#[tauri::command]
+fn list_projects(app: tauri::AppHandle, query: Option<project_manager::ProjectQuery>) -> Result<Vec<project_manager::ProjectInfo>, String> {
+    let manager = project_manager(&app)?;
+    manager.list_projects(&query.unwrap_or_default())
+}
+
 #[tauri::command]
-fn list_projects() -> Result<Vec<project_manager::ProjectInfo>, String> {
-    let manager = ProjectManager::new()?;
-    manager.list_projects()
+fn set_project_thumbnail(app: tauri::AppHandle, path: String, image_path: Option<String>) -> Result<Option<String>, String> {
+    let manager = project_manager(&app)?;
+    manager.set_project_thumbnail(&path, image_path.as_deref())
 }
 
 #[tauri::command]
-fn create_project(name: String) -> Result<String, String> {
-    let manager = ProjectManager::new()?;
-    manager.create_project(&name)
+fn set_project_tags(app: tauri::AppHandle, path: String, tags: Vec<String>) -> Result<Vec<String>, String> {
+    let manager = project_manager(&app)?;
+    manager.set_project_tags(&path, &tags)
 }
 
 #[tauri::command]
-fn delete_project(path: String) -> Result<(), String> {
-    let manager = ProjectManager::new()?;
+fn list_project_locations(app: tauri::AppHandle) -> Result<Vec<project_locations::LocationInfo>, String> {
+    let locations = ProjectLocations::load(&editor_config_path(&app)?)?;
+    Ok(locations.list())
+}
+
+#[tauri::command]
+fn add_project_location(app: tauri::AppHandle, path: String, kind: LocationKind) -> Result<String, String> {
+    let mut locations = ProjectLocations::load(&editor_config_path(&app)?)?;
+    let path = locations.add(&path, kind)?;
+    locations.save()?;
+    Ok(path)
+}
+
+#[tauri::command]
+fn remove_project_location(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    let mut locations = ProjectLocations::load(&editor_config_path(&app)?)?;
+    if !locations.remove(&path) {
+        return Err("Location is not registered".to_string());
+    }
+    locations.save()
+}
+
+fn template_registry(app: &tauri::AppHandle) -> TemplateRegistry {
+    let user_dir = app.path().app_data_dir()
+        .ok()
+        .map(|dir| dir.join("templates"));
+    TemplateRegistry::new(app.path().resource_dir().ok(), user_dir)
+}
+
+#[tauri::command]
+fn list_project_templates(app: tauri::AppHandle) -> Result<project_templates::TemplateList, String> {
+    template_registry(&app).list_templates()
+}
+
+#[tauri::command]
+fn create_project(app: tauri::AppHandle, name: String, template: Option<String>) -> Result<String, String> {
+    let manager = project_manager(&app)?;
+    let template_dir = match template {
+        Some(id) => template_registry(&app).find_template(&id)?,
+        None => None,
+    };
+    manager.create_project(&name, template_dir.as_deref())
+}
+
+#[tauri::command]
+fn delete_project(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    let manager = project_manager(&app)?;
     manager.delete_project(&path)
 }
 
+#[tauri::command]
+fn list_trashed_projects(app: tauri::AppHandle) -> Result<Vec<project_manager::TrashedProject>, String> {
+    let manager = project_manager(&app)?;
+    manager.list_trashed_projects()
+}
+
+#[tauri::command]
+fn restore_project(app: tauri::AppHandle, id: String) -> Result<String, String> {
+    let manager = project_manager(&app)?;
+    manager.restore_project(&id)
+}
+
+#[tauri::command]
+fn purge_trash(app: tauri::AppHandle, max_age_days: Option<u64>) -> Result<usize, String> {
+    let manager = project_manager(&app)?;
+    manager.purge_trash(max_age_days)
+}
+
+#[tauri::command]
+fn duplicate_project(app: tauri::AppHandle, path: String, new_name: Option<String>) -> Result<String, String> {
+    let manager = project_manager(&app)?;
+    manager.duplicate_project(&path, new_name.as_deref())
+}
+
+#[tauri::command]
+fn rename_project(app: tauri::AppHandle, path: String, new_name: String) -> Result<String, String> {
+    let manager = project_manager(&app)?;
+    manager.rename_project(&path, &new_name)
+}
+
+#[tauri::command]
+fn export_project(app: tauri::AppHandle, path: String, destination: String) -> Result<(), String> {
+    let manager = project_manager(&app)?;
+    manager.export_project(&path, &destination)
+}
+
+#[tauri::command]
+fn import_project(app: tauri::AppHandle, archive_path: String) -> Result<String, String> {
+    let manager = project_manager(&app)?;
+    manager.import_project(&archive_path)
+}
+
+#[tauri::command]
+fn check_project(state: State<AppState>, project_path: String) -> Result<Vec<project_check::ProjectProblem>, String> {
+    project_check::check_project(&state.project_metadata, std::path::Path::new(&project_path))
+}
+
+#[tauri::command]
+fn repair_project(state: State<AppState>, project_path: String) -> Result<project_check::RepairReport, String> {
+    ensure_writable(&state, &project_path)?;
+    project_check::repair_project(&state.project_metadata, std::path::Path::new(&project_path))
+}
+
+/// Returns scene file content unchanged. Content the scene model doesn't
+/// accept is only reported: refusing it here would leave no way to open the
+/// scene and fix it. Saves are still validated.
+fn checked_scene_content(content: String) -> Result<String, String> {
+    if content.trim().is_empty() {
+        return Err("File is empty".to_string());
+    }
+    if let Err(e) = SceneDocument::from_json_str(&content) {
+        log::warn!("[Editor] {}", e);
+    }
+    Ok(content)
+}
+
 #[tauri::command]
 fn read_scene_file(project_path: String, scene_name: Option<String>) -> Result<String, String> {
+    Ok(read_scene_content(&project_path, scene_name)?.content)
+}
+
+/// Like `read_scene_file`, but returns the scene as CBOR bytes (see
+/// `scene_codec`), sparing large scenes the trip through a JSON string.
+/// The editor decodes them with `libs/cbor.js`.
+#[tauri::command]
+fn read_scene_binary(project_path: String, scene_name: Option<String>) -> Result<tauri::ipc::Response, String> {
+    let content = read_scene_content(&project_path, scene_name)?.content;
+    Ok(tauri::ipc::Response::new(scene_codec::encode_scene(&content)?))
+}
+
+/// Like `read_scene_file`, along with the revision to pass back as
+/// `expected_revision` when saving.
+#[tauri::command]
+fn read_scene_file_with_revision(project_path: String, scene_name: Option<String>) -> Result<revision::Revisioned, String> {
+    read_scene_content(&project_path, scene_name)
+}
+
+/// The revision `read_scene_file_with_revision` would return, for callers
+/// reading the scene with `read_scene_binary`. Read it before the scene: a
+/// change in between then shows up as a conflict on save.
+#[tauri::command]
+fn read_scene_revision(project_path: String, scene_name: Option<String>) -> Result<Option<String>, String> {
+    let filename = scene_filename(scene_name)?;
+    revision::scene_revision(std::path::Path::new(&project_path), &filename)
+}
+
+/// Scene names default to the legacy `scene.json`, and must name a file
+/// inside `scenes/`.
+fn scene_filename(scene_name: Option<String>) -> Result<String, String> {
+    let filename = scene_name.unwrap_or_else(|| "scene.json".to_string());
+    if !project_metadata::is_valid_scene_name(&filename) {
+        return Err(format!("Invalid scene name '{}'", filename));
+    }
+    Ok(filename)
+}
+
+fn read_scene_content(project_path: &str, scene_name: Option<String>) -> Result<revision::Revisioned, String> {
     use std::fs;
-    use std::path::PathBuf;
+    use std::path::Path;
     
-    let filename = scene_name.unwrap_or_else(|| "scene.json".to_string());
-    let path = PathBuf::from(&project_path).join("scenes").join(&filename);
+    let filename = scene_filename(scene_name)?;
+    let project_dir = Path::new(project_path);
     
-    if !path.exists() {
-        let legacy_path = PathBuf::from(&project_path).join("scene.json");
+    if !scene_store::scene_exists(project_dir, &filename) {
+        // Projects from before `scenes/` keep their scene in the root. It has
+        // no revision yet: the first save moves it into `scenes/`.
+        let legacy_path = project_dir.join("scene.json");
         if legacy_path.exists() {
-            return match fs::read_to_string(&legacy_path) {
-                Ok(content) => {
-                    if content.trim().is_empty() {
-                        Err("File is empty".to_string())
-                    } else {
-                        Ok(content)
-                    }
-                },
-                Err(e) => Err(format!("Failed to read file: {}", e))
-            };
+            let content = fs::read_to_string(&legacy_path)
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+            return Ok(revision::Revisioned { content: checked_scene_content(content)?, revision: None });
         }
         return Err("File not found".to_string());
     }
     
-    match fs::read_to_string(&path) {
-        Ok(content) => {
-            if content.trim().is_empty() {
-                Err("File is empty".to_string())
-            } else {
-                Ok(content)
-            }
-        },
-        Err(e) => {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                Err("File not found".to_string())
-            } else {
-                Err(format!("Failed to read file: {}", e))
-            }
-        }
-    }
+    // Split scenes are assembled into a single document here, and the undo
+    // history kept beside the scene is put back in. The revision is of the
+    // scene as stored, without the history.
+    let content = scene_store::read_scene(project_dir, &filename)?;
+    let revision = revision::revision_of(content.as_bytes());
+    let content = checked_scene_content(undo_history::attach(project_dir, &filename, content)?)?;
+    
+    Ok(revision::Revisioned { content, revision: Some(revision) })
 }
 
 #[tauri::command]
-fn copy_scene_to_engine(project_path: String) -> Result<(), String> {
+fn copy_scene_to_engine(state: State<AppState>, project_path: String) -> Result<(), String> {
     use std::fs;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     
-    let project_json_path = PathBuf::from(&project_path).join("project.json");
-    let default_scene = if project_json_path.exists() {
-        if let Ok(content) = fs::read_to_string(&project_json_path) {
-            if let Ok(metadata) = serde_json::from_str::<serde_json::Value>(&content) {
-                metadata.get("defaultScene")
-                    .and_then(|s| s.as_str())
-                    .map(|s| s.to_string())
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+    let default_scene = state.project_metadata.load_optional(Path::new(&project_path))?
+        .map(|metadata| metadata.default_scene);
     
-	let scene_filename = default_scene.unwrap_or_else(|| "Main.json".to_string());
-	let mut scene_path = PathBuf::from(&project_path).join("scenes").join(&scene_filename);
+	let mut scene_filename = default_scene.unwrap_or_else(|| project_metadata::DEFAULT_SCENE.to_string());
+	let project_dir = PathBuf::from(&project_path);
 	
-	if !scene_path.exists() {
-		let legacy_path = PathBuf::from(&project_path).join("scene.json");
+	if !scene_store::scene_exists(&project_dir, &scene_filename) {
+		let legacy_path = project_dir.join("scene.json");
 		if !legacy_path.exists() {
-			scene_path = PathBuf::from(&project_path).join("scenes").join("Main.json");
-			if !scene_path.exists() {
+			scene_filename = "Main.json".to_string();
+			if !scene_store::scene_exists(&project_dir, &scene_filename) {
 				return Err("Scene file not found".to_string());
 			}
 		} else {
-			let _ = fs::copy(&legacy_path, &scene_path);
+			let _ = fs::copy(&legacy_path, project_dir.join("scenes").join(&scene_filename));
 		}
 	}
     
+    let content = scene_store::read_scene(&project_dir, &scene_filename)?;
+    let (content, _) = undo_history::detach(&content)?;
+    
     let engine_public = std::env::current_dir()
         .unwrap()
         .parent()
@@ -153,16 +360,15 @@ fn copy_scene_to_engine(project_path: String) -> Result<(), String> {
     
     let dest_path = engine_public.join("scene.json");
     
-    fs::copy(&scene_path, &dest_path)
+    fs::write(&dest_path, content.as_bytes())
         .map_err(|e| format!("Failed to copy scene.json to engine: {}", e))?;
     
     Ok(())
 }
 
 #[tauri::command]
-fn list_scenes(project_path: String) -> Result<Vec<serde_json::Value>, String> {
-    use std::fs;
-    use std::path::PathBuf;
+fn list_scenes(state: State<AppState>, project_path: String, stats: Option<bool>) -> Result<Vec<serde_json::Value>, String> {
+    use std::path::{Path, PathBuf};
     
     let scenes_dir = PathBuf::from(&project_path).join("scenes");
     
@@ -179,45 +385,35 @@ fn list_scenes(project_path: String) -> Result<Vec<serde_json::Value>, String> {
 	}
 	
 	let mut scenes = Vec::new();
-	let project_json_path = PathBuf::from(&project_path).join("project.json");
-	let mut scenes_config = serde_json::Map::new();
-	let mut default_scene = "Main.json".to_string();
-    
-    if project_json_path.exists() {
-        if let Ok(content) = fs::read_to_string(&project_json_path) {
-            if let Ok(metadata) = serde_json::from_str::<serde_json::Value>(&content) {
-                if let Some(ds) = metadata.get("defaultScene").and_then(|s| s.as_str()) {
-                    default_scene = ds.to_string();
-                }
-                if let Some(sc) = metadata.get("scenes").and_then(|s| s.as_object()) {
-                    scenes_config = sc.clone();
-                }
-            }
-        }
-    }
-    
-    let entries = fs::read_dir(&scenes_dir)
-        .map_err(|e| format!("Failed to read scenes directory: {}", e))?;
-    
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let path = entry.path();
+	let metadata = state.project_metadata.load_optional(Path::new(&project_path))?;
+	let default_scene = metadata.as_ref()
+		.map(|m| m.default_scene.clone())
+		.unwrap_or_else(|| project_metadata::DEFAULT_SCENE.to_string());
+    
+    // Split scenes are listed under their `.json` name like any other.
+    for filename in scene_store::list_scene_names(Path::new(&project_path)) {
+        let include_in_build = metadata.as_ref()
+            .map(|m| m.scene_config(&filename).include_in_build)
+            .unwrap_or(true);
         
-        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
-            if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
-                let scene_config = scenes_config.get(filename);
-                let include_in_build = scene_config
-                    .and_then(|c| c.get("includeInBuild"))
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(true);
-                
-                scenes.push(serde_json::json!({
-                    "name": filename,
-                    "isDefault": filename == default_scene,
-                    "includeInBuild": include_in_build
-                }));
-            }
+        let mut scene = serde_json::json!({
+            "name": filename,
+            "isDefault": filename == default_scene,
+            "includeInBuild": include_in_build
+        });
+        
+        // Only the outline of each scene is read; a scene that can't be
+        // read is still listed, without stats.
+        if stats.unwrap_or(false) {
+            let summary = scene_store::read_scene(Path::new(&project_path), &filename)
+                .and_then(|content| scene_stats::summarize_scene(&content));
+            scene["stats"] = match summary {
+                Ok(summary) => serde_json::to_value(summary).unwrap_or_default(),
+                Err(_) => serde_json::Value::Null,
+            };
         }
+        
+        scenes.push(scene);
     }
     
     scenes.sort_by(|a, b| {
@@ -238,102 +434,248 @@ fn list_scenes(project_path: String) -> Result<Vec<serde_json::Value>, String> {
     Ok(scenes)
 }
 
+/// Object, light, geometry and texture counts of a scene, for keeping an
+/// eye on its cost without opening it.
 #[tauri::command]
-fn delete_scene_file(project_path: String, scene_name: String) -> Result<(), String> {
-    use std::fs;
-    use std::path::PathBuf;
-    
-    let path = PathBuf::from(&project_path).join("scenes").join(&scene_name);
-    
-    if !path.exists() {
-        return Err("Scene file not found".to_string());
-    }
+fn scene_stats(project_path: String, scene_name: String) -> Result<scene_stats::SceneStats, String> {
+    let content = scene_store::read_scene(std::path::Path::new(&project_path), &scene_name)?;
+    scene_stats::scene_stats(&content)
+}
+
+/// Finds objects across all scenes of a project by name, type, userData,
+/// script, material or texture.
+#[tauri::command]
+fn query_scenes(project_path: String, query: scene_query::SceneQuery) -> Result<scene_query::QueryResult, String> {
+    scene_query::query_scenes(std::path::Path::new(&project_path), &query)
+}
+
+/// Asset references of every scene that don't resolve against `assets/`,
+/// with the objects using them.
+#[tauri::command]
+fn check_asset_references(project_path: String) -> Result<asset_references::AssetReferenceReport, String> {
+    asset_references::asset_reference_report(std::path::Path::new(&project_path))
+}
+
+#[tauri::command]
+fn delete_scene_file(state: State<AppState>, project_path: String, scene_name: String) -> Result<(), String> {
+    use std::path::Path;
     
-    fs::remove_file(&path)
-        .map_err(|e| format!("Failed to delete scene file: {}", e))?;
+    ensure_writable(&state, &project_path)?;
     
-    Ok(())
+    state.scene_locks.with_lock(Path::new(&project_path), &scene_name, || {
+        if !scene_store::scene_exists(Path::new(&project_path), &scene_name) {
+            return Err("Scene file not found".to_string());
+        }
+        
+        scene_store::delete_scene(Path::new(&project_path), &scene_name)?;
+        scene_recovery::clear(Path::new(&project_path), &scene_name)?;
+        undo_history::clear(Path::new(&project_path), &scene_name)
+    })
 }
 
 #[tauri::command]
-fn rename_scene_file(project_path: String, old_name: String, new_name: String) -> Result<(), String> {
-    use std::fs;
-    use std::path::PathBuf;
+fn rename_scene_file(state: State<AppState>, project_path: String, old_name: String, new_name: String) -> Result<(), String> {
+    use std::path::Path;
     
-    let scenes_dir = PathBuf::from(&project_path).join("scenes");
-    let old_path = scenes_dir.join(&old_name);
-    let new_path = scenes_dir.join(&new_name);
+    ensure_writable(&state, &project_path)?;
+    
+    if !project_metadata::is_valid_scene_name(&new_name) {
+        return Err(format!("Invalid scene name '{}'", new_name));
+    }
+    
+    // Fail on an unreadable project.json before any file is renamed.
+    state.project_metadata.load_optional(Path::new(&project_path))?;
     
-    if !old_path.exists() {
+    if !scene_store::scene_exists(Path::new(&project_path), &old_name) {
         return Err("Scene file not found".to_string());
     }
     
-    if new_path.exists() {
+    if scene_store::scene_exists(Path::new(&project_path), &new_name) {
         return Err("A scene with that name already exists".to_string());
     }
     
-    fs::rename(&old_path, &new_path)
-        .map_err(|e| format!("Failed to rename scene file: {}", e))?;
+    scene_store::rename_scene(Path::new(&project_path), &old_name, &new_name)?;
+    scene_recovery::rename_journal(Path::new(&project_path), &old_name, &new_name);
+    scene_history::rename_history(Path::new(&project_path), &old_name, &new_name);
+    undo_history::rename(Path::new(&project_path), &old_name, &new_name);
     
-    let project_json_path = PathBuf::from(&project_path).join("project.json");
-    if project_json_path.exists() {
-        if let Ok(content) = fs::read_to_string(&project_json_path) {
-            if let Ok(mut metadata) = serde_json::from_str::<serde_json::Value>(&content) {
-                if let Some(default_scene) = metadata.get("defaultScene").and_then(|s| s.as_str()) {
-                    if default_scene == old_name {
-                        metadata["defaultScene"] = serde_json::Value::String(new_name.clone());
-                    }
-                }
-                
-                if let Some(scenes) = metadata.get_mut("scenes").and_then(|s| s.as_object_mut()) {
-                    if let Some(scene_config) = scenes.remove(&old_name) {
-                        scenes.insert(new_name.clone(), scene_config);
-                    }
-                }
-                
-                if let Ok(updated_content) = serde_json::to_string_pretty(&metadata) {
-                    let _ = fs::write(&project_json_path, updated_content);
-                }
-            }
+    state.project_metadata.update_optional(Path::new(&project_path), |metadata| {
+        if metadata.default_scene == old_name {
+            metadata.default_scene = new_name.clone();
         }
-    }
+        
+        if let Some(scene_config) = metadata.scenes.remove(&old_name) {
+            metadata.scenes.insert(new_name.clone(), scene_config);
+        }
+        
+        Ok(())
+    })?;
     
     Ok(())
 }
 
+/// Saves a scene and returns its new revision. With `expected_revision`,
+/// the save fails with a `CONFLICT: ` error if the scene changed on disk
+/// since it was read.
 #[tauri::command]
-fn write_scene_file(project_path: String, content: String, scene_name: Option<String>) -> Result<(), String> {
-    use std::fs;
-    use std::path::PathBuf;
-    use std::time::{SystemTime, UNIX_EPOCH};
+fn write_scene_file(state: State<AppState>, project_path: String, content: String, scene_name: Option<String>, label: Option<String>, expected_revision: Option<String>) -> Result<String, String> {
+    use std::path::Path;
     
-    let scenes_dir = PathBuf::from(&project_path).join("scenes");
-    if !scenes_dir.exists() {
-        fs::create_dir_all(&scenes_dir)
-            .map_err(|e| format!("Failed to create scenes directory: {}", e))?;
-    }
+    ensure_writable(&state, &project_path)?;
     
-    let filename = scene_name.unwrap_or_else(|| "scene.json".to_string());
-    let path = scenes_dir.join(&filename);
-    fs::write(&path, content)
-        .map_err(|e| format!("Failed to write file: {}", e))?;
+    // Refuse corrupted content rather than overwriting a good file with it.
+    SceneDocument::from_json_str(&content)?;
     
-    let project_json_path = PathBuf::from(&project_path).join("project.json");
-    if project_json_path.exists() {
-        if let Ok(project_content) = fs::read_to_string(&project_json_path) {
-            if let Ok(mut metadata) = serde_json::from_str::<serde_json::Value>(&project_content) {
-                let now = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs();
-                metadata["modified"] = serde_json::Value::Number(now.into());
-                
-                if let Ok(updated_content) = serde_json::to_string_pretty(&metadata) {
-                    let _ = fs::write(&project_json_path, updated_content);
-                }
+    // The undo history is kept out of the scene file, so it doesn't end up
+    // in every save and every commit.
+    let (content, history) = undo_history::detach(&content)?;
+    
+    let filename = scene_filename(scene_name)?;
+    
+    // Checked and written under the scene's lock, so a concurrent save can't
+    // land between the check and the write.
+    let revision = state.scene_locks.with_lock(Path::new(&project_path), &filename, || {
+        if expected_revision.is_some() {
+            let current = revision::scene_revision(Path::new(&project_path), &filename)?;
+            revision::check_revision(&format!("scenes/{}", filename), current.as_deref(), expected_revision.as_deref())?;
+        }
+        
+        scene_store::write_scene(&state.project_metadata, Path::new(&project_path), &filename, &content)?;
+        
+        if let Some(history) = history {
+            if let Err(e) = undo_history::save(Path::new(&project_path), &filename, history) {
+                log::warn!("[Editor] Failed to save undo history: {}", e);
             }
         }
-    }
+        
+        // The scene is saved at this point; losing a history entry is not
+        // worth failing the save over.
+        if let Err(e) = scene_history::record_snapshot(Path::new(&project_path), &filename, &content, label.as_deref()) {
+            log::warn!("[Editor] Failed to record scene history: {}", e);
+        }
+        
+        // Read back, as the stored form depends on the scene's layout.
+        revision::scene_revision(Path::new(&project_path), &filename)?
+            .ok_or_else(|| "Scene file not found after saving".to_string())
+    })?;
+    
+    state.project_metadata.touch(Path::new(&project_path))?;
+    
+    Ok(revision)
+}
+
+/// Compares two scene documents by object and resource uuid rather than as
+/// text.
+#[tauri::command]
+fn diff_scenes(old_content: String, new_content: String) -> Result<scene_diff::SceneDiff, String> {
+    scene_diff::diff_scene_json(&old_content, &new_content)
+}
+
+#[tauri::command]
+fn merge_scenes(base: String, ours: String, theirs: String) -> Result<scene_merge::MergeResult, String> {
+    scene_merge::merge_scenes(&base, &ours, &theirs)
+}
+
+#[tauri::command]
+fn list_scene_snapshots(project_path: String, scene_name: String) -> Vec<scene_history::Snapshot> {
+    scene_history::list_snapshots(std::path::Path::new(&project_path), &scene_name)
+}
+
+/// Diffs snapshot `from` against snapshot `to`, or against the saved scene
+/// if `to` is omitted.
+#[tauri::command]
+fn diff_scene_snapshots(project_path: String, scene_name: String, from: u64, to: Option<u64>) -> Result<scene_diff::SceneDiff, String> {
+    scene_history::diff_snapshots(std::path::Path::new(&project_path), &scene_name, from, to)
+}
+
+#[tauri::command]
+fn restore_scene_snapshot(state: State<AppState>, project_path: String, scene_name: String, id: u64) -> Result<(), String> {
+    use std::path::Path;
+    
+    ensure_writable(&state, &project_path)?;
+    
+    state.scene_locks.with_lock(Path::new(&project_path), &scene_name, || {
+        scene_history::restore_snapshot(&state.project_metadata, Path::new(&project_path), &scene_name, id)
+    })?;
+    
+    state.project_metadata.touch(Path::new(&project_path))?;
+    
+    Ok(())
+}
+
+/// Autosave channel: keeps the editor's unsaved state in the project's
+/// recovery journal without touching the scene file.
+#[tauri::command]
+fn autosave_scene(state: State<AppState>, project_path: String, content: String, scene_name: Option<String>) -> Result<(), String> {
+    ensure_writable(&state, &project_path)?;
+    
+    let filename = scene_filename(scene_name)?;
+    scene_recovery::autosave(std::path::Path::new(&project_path), &filename, &content)
+}
+
+#[tauri::command]
+fn list_scene_recoveries(project_path: String) -> Vec<scene_recovery::RecoveryInfo> {
+    scene_recovery::find_recoverable(std::path::Path::new(&project_path), false)
+}
+
+#[tauri::command]
+fn get_scene_recovery(project_path: String, scene_name: String) -> Result<scene_recovery::RecoveredScene, String> {
+    scene_recovery::read_journal(std::path::Path::new(&project_path), &scene_name)
+}
+
+#[tauri::command]
+fn discard_scene_recovery(state: State<AppState>, project_path: String, scene_name: String) -> Result<(), String> {
+    ensure_writable(&state, &project_path)?;
+    
+    scene_recovery::clear(std::path::Path::new(&project_path), &scene_name)
+}
+
+/// Switches the project between single-file and split scene storage and
+/// rewrites its existing scenes in the new layout.
+#[tauri::command]
+fn set_scene_storage(state: State<AppState>, project_path: String, storage: project_metadata::SceneStorage) -> Result<(), String> {
+    use std::path::Path;
+    
+    ensure_writable(&state, &project_path)?;
+    
+    state.project_metadata.update(Path::new(&project_path), |metadata| {
+        scene_store::convert_scenes(Path::new(&project_path), storage, metadata.scene_encoding)?;
+        metadata.scene_storage = storage;
+        Ok(())
+    })
+}
+
+/// Switches the project between JSON and binary (CBOR) scene files and
+/// rewrites its existing scenes in the new encoding.
+#[tauri::command]
+fn set_scene_encoding(state: State<AppState>, project_path: String, encoding: project_metadata::SceneEncoding) -> Result<(), String> {
+    use std::path::Path;
+    
+    ensure_writable(&state, &project_path)?;
+    
+    state.project_metadata.update(Path::new(&project_path), |metadata| {
+        scene_store::convert_scenes(Path::new(&project_path), metadata.scene_storage, encoding)?;
+        metadata.scene_encoding = encoding;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn list_scene_backups(project_path: String, scene_name: String) -> Vec<scene_store::SceneBackup> {
+    scene_store::list_backups(std::path::Path::new(&project_path), &scene_name)
+}
+
+#[tauri::command]
+fn restore_scene_backup(state: State<AppState>, project_path: String, scene_name: String, index: usize) -> Result<(), String> {
+    use std::path::Path;
+    
+    ensure_writable(&state, &project_path)?;
+    
+    state.scene_locks.with_lock(Path::new(&project_path), &scene_name, || {
+        scene_store::restore_backup(&state.project_metadata, Path::new(&project_path), &scene_name, index)
+    })?;
+    
+    state.project_metadata.touch(Path::new(&project_path))?;
     
     Ok(())
 }
@@ -342,9 +684,7 @@ fn write_scene_file(project_path: String, content: String, scene_name: Option<St
 fn read_editor_config(app: tauri::AppHandle) -> Result<String, String> {
     use std::fs;
     
-    let config_dir = app.path().app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    let config_path = config_dir.join("editor.json");
+    let config_path = editor_config_path(&app)?;
     
     match fs::read_to_string(&config_path) {
         Ok(content) => Ok(content),
@@ -362,50 +702,62 @@ fn read_editor_config(app: tauri::AppHandle) -> Result<String, String> {
 fn write_editor_config(app: tauri::AppHandle, content: String) -> Result<(), String> {
     use std::fs;
     
-    let config_dir = app.path().app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    let config_path = editor_config_path(&app)?;
+    let config_dir = config_path.parent()
+        .ok_or("Invalid editor config path")?;
     
     if !config_dir.exists() {
-        fs::create_dir_all(&config_dir)
+        fs::create_dir_all(config_dir)
             .map_err(|e| format!("Failed to create config directory: {}", e))?;
     }
     
-    let config_path = config_dir.join("editor.json");
-    fs::write(&config_path, content)
+    // Project locations are managed through their own commands; keep the
+    // stored value rather than whatever the frontend sends back.
+    let mut config = serde_json::from_str::<serde_json::Value>(&content)
+        .map_err(|e| format!("Invalid editor config: {}", e))?;
+    if let Some(config) = config.as_object_mut() {
+        let locations = ProjectLocations::load(&config_path)?;
+        config.insert(
+            project_locations::EDITOR_CONFIG_KEY.to_string(),
+            serde_json::to_value(&locations)
+                .map_err(|e| format!("Failed to serialize project locations: {}", e))?,
+        );
+    }
+    
+    let content = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize editor config: {}", e))?;
+    write_atomic(&config_path, content)
         .map_err(|e| format!("Failed to write editor config: {}", e))?;
     
     Ok(())
 }
 
 #[tauri::command]
-fn read_project_config(project_path: String) -> Result<String, String> {
-    use std::fs;
-    use std::path::PathBuf;
-    
-    let config_path = PathBuf::from(&project_path).join("project.json");
-    
-    match fs::read_to_string(&config_path) {
-        Ok(content) => Ok(content),
-        Err(e) => {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                Ok("{}".to_string())
-            } else {
-                Err(format!("Failed to read project config: {}", e))
-            }
-        }
-    }
+fn read_project_config(state: State<AppState>, project_path: String) -> Result<String, String> {
+    let config = state.project_metadata.read_content(std::path::Path::new(&project_path))?;
+    Ok(config.map_or_else(|| "{}".to_string(), |config| config.content))
 }
 
+/// Like `read_project_config`, along with the revision to pass back as
+/// `expected_revision` when saving.
 #[tauri::command]
-fn write_project_config(project_path: String, content: String) -> Result<(), String> {
-    use std::fs;
-    use std::path::PathBuf;
+fn read_project_config_with_revision(state: State<AppState>, project_path: String) -> Result<revision::Revisioned, String> {
+    let config = state.project_metadata.read_content(std::path::Path::new(&project_path))?;
+    Ok(config.unwrap_or_else(|| revision::Revisioned { content: "{}".to_string(), revision: None }))
+}
+
+/// Saves `project.json` and returns its new revision. With
+/// `expected_revision`, the save fails with a `CONFLICT: ` error if the file
+/// changed on disk since it was read.
+#[tauri::command]
+fn write_project_config(state: State<AppState>, project_path: String, content: String, expected_revision: Option<String>) -> Result<String, String> {
+    use std::path::Path;
     
-    let config_path = PathBuf::from(&project_path).join("project.json");
-    fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write project config: {}", e))?;
+    ensure_writable(&state, &project_path)?;
     
-    Ok(())
+    let (metadata, _) = ProjectMetadata::from_json_str(&content)?;
+    
+    state.project_metadata.replace(Path::new(&project_path), metadata, expected_revision.as_deref())
 }
 
 #[tauri::command]
@@ -450,17 +802,23 @@ async fn get_file_metadata(project_path: String, asset_path: String) -> Result<O
 }
 
 #[tauri::command]
-async fn write_asset_file(project_path: String, asset_path: String, content: Vec<u8>) -> Result<(), String> {
+async fn write_asset_file(state: State<'_, AppState>, project_path: String, asset_path: String, content: Vec<u8>) -> Result<(), String> {
     use std::path::PathBuf;
     
+    ensure_writable(&state, &project_path)?;
+    
     let assets_dir = PathBuf::from(&project_path).join("assets");
     let full_path = assets_dir.join(&asset_path);
     
     if let Some(parent) = full_path.parent() {
-        tokio::fs::create_dir_all(parent).await
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
+        if !parent.exists() {
+            project_watcher::note_own_write(parent);
+            tokio::fs::create_dir_all(parent).await
+                .map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
     }
     
+    project_watcher::note_own_write(&full_path);
     tokio::fs::write(&full_path, content).await
         .map_err(|e| format!("Failed to write file: {}", e))?;
     
@@ -468,9 +826,11 @@ async fn write_asset_file(project_path: String, asset_path: String, content: Vec
 }
 
 #[tauri::command]
-async fn write_build_file(project_path: String, file_path: String, content: Vec<u8>) -> Result<(), String> {
+async fn write_build_file(state: State<'_, AppState>, project_path: String, file_path: String, content: Vec<u8>) -> Result<(), String> {
     use std::path::PathBuf;
     
+    ensure_writable(&state, &project_path)?;
+    
     let build_dir = PathBuf::from(&project_path).join("build");
     let full_path = build_dir.join(&file_path);
     
@@ -485,6 +845,49 @@ async fn write_build_file(project_path: String, file_path: String, content: Vec<
     Ok(())
 }
 
+/// Writes the published scene (`app.json`) into the build. Only with
+/// `binary` set is it written as `app.cbor` instead, which the published
+/// player loads in preference to `app.json`; the other one is removed so a
+/// stale copy can't be picked up. Any undo history is left out. Returns the
+/// path written, relative to `build/`.
+#[tauri::command]
+async fn write_build_scene(state: State<'_, AppState>, project_path: String, file_path: String, content: String, binary: Option<bool>) -> Result<String, String> {
+    use std::path::{Path, PathBuf};
+    
+    ensure_writable(&state, &project_path)?;
+    
+    let (content, _) = undo_history::detach(&content)?;
+    
+    let binary_path = Path::new(&file_path)
+        .with_extension(scene_codec::BINARY_SCENE_EXTENSION)
+        .to_string_lossy()
+        .to_string();
+    let (file_path, other_path, bytes) = if binary == Some(true) {
+        (binary_path, file_path, scene_codec::encode_scene(&content)?)
+    } else {
+        (file_path, binary_path, content.into_owned().into_bytes())
+    };
+    
+    let build_dir = PathBuf::from(&project_path).join("build");
+    let full_path = build_dir.join(&file_path);
+    
+    if let Some(parent) = full_path.parent() {
+        tokio::fs::create_dir_all(parent).await
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    
+    tokio::fs::write(&full_path, bytes).await
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    
+    match tokio::fs::remove_file(build_dir.join(&other_path)).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to remove {}: {}", other_path, e)),
+    }
+    
+    Ok(file_path)
+}
+
 #[tauri::command]
 async fn read_editor_template_file(file_path: String) -> Result<String, String> {
     let current_dir = std::env::current_dir()
@@ -502,10 +905,18 @@ async fn read_editor_template_file(file_path: String) -> Result<String, String>
 }
 
 #[tauri::command]
-async fn copy_assets_to_build(project_path: String) -> Result<(), String> {
+async fn copy_assets_to_build(state: State<'_, AppState>, project_path: String, check_references: Option<bool>) -> Result<(), String> {
     use std::path::PathBuf;
     use tokio::fs;
     
+    ensure_writable(&state, &project_path)?;
+    
+    // Refuse to ship a build whose scenes point at assets that aren't there,
+    // unless the caller explicitly opts out.
+    if check_references.unwrap_or(true) {
+        asset_references::ensure_build_references(&state.project_metadata, std::path::Path::new(&project_path))?;
+    }
+    
     let assets_dir = PathBuf::from(&project_path).join("assets");
     let build_assets_dir = PathBuf::from(&project_path).join("build").join("assets");
     
@@ -559,22 +970,10 @@ async fn copy_assets_to_build(project_path: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-fn read_project_metadata(project_path: String) -> Result<String, String> {
-    use std::fs;
-    use std::path::PathBuf;
-    
-    let metadata_path = PathBuf::from(&project_path).join("project.json");
-    
-    match fs::read_to_string(&metadata_path) {
-        Ok(content) => Ok(content),
-        Err(e) => {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                Err("File not found".to_string())
-            } else {
-                Err(format!("Failed to read file: {}", e))
-            }
-        }
-    }
+fn read_project_metadata(state: State<AppState>, project_path: String) -> Result<String, String> {
+    state.project_metadata.read_content(std::path::Path::new(&project_path))?
+        .map(|metadata| metadata.content)
+        .ok_or_else(|| "File not found".to_string())
 }
 
 #[tauri::command]
@@ -641,10 +1040,12 @@ async fn open_file(project_path: String, asset_path: String) -> Result<(), Strin
 }
 
 #[tauri::command]
-async fn run_npm_install(project_path: String) -> Result<(), String> {
+async fn run_npm_install(state: State<'_, AppState>, project_path: String) -> Result<(), String> {
     use std::path::PathBuf;
     use std::process::Command;
 
+    ensure_writable(&state, &project_path)?;
+
     let path = PathBuf::from(&project_path);
     if !path.exists() || !path.is_dir() {
         return Err("Project path does not exist or is not a directory".to_string());
@@ -689,25 +1090,29 @@ async fn run_npm_install(project_path: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-async fn write_assets_metadata(project_path: String, content: String) -> Result<(), String> {
+async fn write_assets_metadata(state: State<'_, AppState>, project_path: String, content: String) -> Result<(), String> {
     use std::path::PathBuf;
     
+    ensure_writable(&state, &project_path)?;
+    
     let assets_dir = PathBuf::from(&project_path).join("assets");
     let metadata_path = assets_dir.join("assets.json");
     
     tokio::fs::create_dir_all(&assets_dir).await
         .map_err(|e| format!("Failed to create assets directory: {}", e))?;
     
-    tokio::fs::write(&metadata_path, content).await
+    write_atomic(&metadata_path, content)
         .map_err(|e| format!("Failed to write metadata: {}", e))?;
     
     Ok(())
 }
 
 #[tauri::command]
-async fn delete_asset_file(project_path: String, asset_path: String) -> Result<(), String> {
+async fn delete_asset_file(state: State<'_, AppState>, project_path: String, asset_path: String) -> Result<(), String> {
     use std::path::PathBuf;
     
+    ensure_writable(&state, &project_path)?;
+    
     let assets_dir = PathBuf::from(&project_path).join("assets");
     let full_path = assets_dir.join(&asset_path);
     
@@ -715,6 +1120,7 @@ async fn delete_asset_file(project_path: String, asset_path: String) -> Result<(
         return Err("File not found".to_string());
     }
     
+    project_watcher::note_own_write(&full_path);
     if full_path.is_dir() {
         tokio::fs::remove_dir_all(&full_path).await
             .map_err(|e| format!("Failed to delete directory: {}", e))?;
@@ -811,8 +1217,88 @@ fn reload_window(app: tauri::AppHandle, window_label: String) -> Result<(), Stri
     }
 }
 
+/// Removes the lock of a project that isn't open in this editor, e.g. one
+/// left behind by an editor that crashed on another machine.
 #[tauri::command]
-fn open_project(app: tauri::AppHandle, path: String) -> Result<(), String> {
+fn break_project_lock(path: String) -> Result<(), String> {
+    project_lock::break_lock(std::path::Path::new(&path))
+}
+
+#[tauri::command]
+fn is_project_read_only(state: State<AppState>) -> bool {
+    state.project_session.lock().as_ref().is_some_and(|session| session.read_only)
+}
+
+#[tauri::command]
+fn get_project_session(state: State<AppState>) -> Option<ProjectSessionInfo> {
+    state.project_session.lock().as_ref().map(|session| ProjectSessionInfo {
+        path: session.path.clone(),
+        read_only: session.read_only,
+    })
+}
+
+/// Opens a project in the editor window. Unless `mode` is `read_only`, the
+/// project is locked for this instance; if another instance holds the lock
+/// this fails with a `PROJECT_LOCKED:` error unless `mode` is `take_over`.
+#[tauri::command]
+fn open_project(app: tauri::AppHandle, state: State<AppState>, path: String, mode: Option<OpenMode>) -> Result<(), String> {
+    if app.get_webview_window("editor").is_some_and(|window| window.is_visible().unwrap_or(false)) {
+        return Err("Editor is already open. Please close it first.".to_string());
+    }
+    
+    // Release the previous session first so its lock can't clobber the new one.
+    state.project_session.lock().take();
+    
+    let mode = mode.unwrap_or_default();
+    let project_dir = std::path::Path::new(&path);
+    let lock = match mode {
+        OpenMode::ReadOnly => None,
+        OpenMode::ReadWrite => Some(ProjectLock::acquire(project_dir, false)?),
+        OpenMode::TakeOver => Some(ProjectLock::acquire(project_dir, true)?),
+    };
+    
+    if project_dir.join(project_metadata::PROJECT_FILE).exists() {
+        if lock.is_some() {
+            state.project_metadata.open(project_dir)?;
+        } else {
+            state.project_metadata.load(project_dir)?;
+        }
+    }
+    
+    // Journals left behind by a crash are offered through
+    // `list_scene_recoveries`; ones that a later save superseded are dropped.
+    let recoverable = scene_recovery::find_recoverable(project_dir, lock.is_some());
+    if !recoverable.is_empty() {
+        log::info!("[Editor] {} scene(s) have unsaved changes to recover", recoverable.len());
+    }
+    
+    // Assets edited in other tools, scenes pulled from git, ... are
+    // reported to the editor as `project-files-changed`.
+    let app_for_watcher = app.clone();
+    let metadata_store = state.project_metadata.clone();
+    let watched_path = project_dir.to_path_buf();
+    let watcher = project_watcher::ProjectWatcher::start(project_dir, move |changes| {
+        if changes.iter().any(|change| change.path == project_metadata::PROJECT_FILE) {
+            metadata_store.invalidate(&watched_path);
+        }
+        if let Some(editor_window) = app_for_watcher.get_webview_window("editor") {
+            let _ = editor_window.emit("project-files-changed", changes);
+        }
+    });
+    let watcher = match watcher {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            log::warn!("[Editor] Project changes won't be detected: {}", e);
+            None
+        }
+    };
+    
+    *state.project_session.lock() = Some(ProjectSession {
+        path: path.clone(),
+        read_only: lock.is_none(),
+        _lock: lock,
+        _watcher: watcher,
+    });
     
     if let Some(existing_window) = app.get_webview_window("editor") {
         match existing_window.is_visible() {
@@ -820,7 +1306,7 @@ fn open_project(app: tauri::AppHandle, path: String) -> Result<(), String> {
                 return Err("Editor is already open. Please close it first.".to_string());
             }
             Ok(false) | Err(_) => {
-                if let Err(e) = copy_scene_to_engine(path.clone()) {
+                if let Err(e) = copy_scene_to_engine(state.clone(), path.clone()) {
                     eprintln!("[Editor] Warning: Failed to copy scene.json to engine: {}", e);
                 }
                 
@@ -903,7 +1389,7 @@ fn open_project(app: tauri::AppHandle, path: String) -> Result<(), String> {
             eprintln!("[Editor] Failed to focus editor window: {}", e);
         }
         
-        if let Err(e) = copy_scene_to_engine(path_clone.clone()) {
+        if let Err(e) = copy_scene_to_engine(app_clone.state::<AppState>(), path_clone.clone()) {
             eprintln!("[Editor] Warning: Failed to copy scene.json to engine: {}", e);
         }
         
@@ -995,6 +1481,12 @@ fn stop_engine(engine_process: &Arc<Mutex<Option<Child>>>) {
     }
 }
 
+/// Runs `merge-scenes <base> <ours> <theirs>`, the git merge driver for
+/// scene files, and returns its exit code.
+pub fn run_merge_driver(args: &[String]) -> i32 {
+    scene_merge::run_merge_driver(args)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let engine_process = Arc::new(Mutex::new(start_engine()));
@@ -1010,6 +1502,9 @@ pub fn run() {
         playing: playing.clone(),
         entities: entities.clone(),
         engine_process: engine_process.clone(),
+        project_session: Arc::new(Mutex::new(None)),
+        project_metadata: Arc::new(ProjectMetadataStore::new()),
+        scene_locks: Arc::new(SceneLocks::new()),
     };
 
     println!("[Editor] Starting Tauri application...");
@@ -1184,14 +1679,52 @@ pub fn run() {
             get_connection_status,
             get_entities,
             list_projects,
+            set_project_thumbnail,
+            set_project_tags,
+            list_project_locations,
+            add_project_location,
+            remove_project_location,
+            list_project_templates,
             create_project,
             delete_project,
+            list_trashed_projects,
+            restore_project,
+            purge_trash,
+            duplicate_project,
+            rename_project,
+            export_project,
+            import_project,
+            check_project,
+            repair_project,
             open_project,
+            break_project_lock,
+            get_project_session,
+            is_project_read_only,
             read_scene_file,
+            read_scene_file_with_revision,
+            read_scene_revision,
             write_scene_file,
             list_scenes,
+            scene_stats,
+            query_scenes,
+            check_asset_references,
             delete_scene_file,
             rename_scene_file,
+            set_scene_storage,
+            set_scene_encoding,
+            read_scene_binary,
+            write_build_scene,
+            list_scene_backups,
+            restore_scene_backup,
+            diff_scenes,
+            merge_scenes,
+            list_scene_snapshots,
+            diff_scene_snapshots,
+            restore_scene_snapshot,
+            autosave_scene,
+            list_scene_recoveries,
+            get_scene_recovery,
+            discard_scene_recovery,
             copy_scene_to_engine,
             read_project_metadata,
             read_asset_file,
@@ -1209,6 +1742,7 @@ pub fn run() {
             write_editor_config,
             read_project_config,
             write_project_config,
+            read_project_config_with_revision,
             reload_window
         ])
         .on_window_event(move |_window, event| {
@@ -1220,6 +1754,10 @@ pub fn run() {
                     let window_label = _window.label();
                     
                     if window_label == "editor" {
+                        if let Some(state) = _window.app_handle().try_state::<AppState>() {
+                            state.project_session.lock().take();
+                        }
+                        
                         let engine_process_clone = engine_process_for_cleanup.clone();
                         std::thread::spawn(move || {
                             stop_engine(&engine_process_clone);
@@ -2,5 +2,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+  let args: Vec<String> = std::env::args().collect();
+  if args.get(1).map(String::as_str) == Some("merge-scenes") {
+    std::process::exit(app_lib::run_merge_driver(&args[2..]));
+  }
+
   app_lib::run();
 }
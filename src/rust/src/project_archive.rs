@@ -0,0 +1,220 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::project_manager::is_non_portable;
+use crate::project_metadata::ProjectMetadata;
+
+/// Packs a project directory into a single zip archive.
+///
+/// Entries are stored relative to the project root. Generated and machine
+/// specific entries (`build/`, `node_modules/`, the lock file) are left out.
+pub fn write_project_archive(project_path: &Path, archive_path: &Path) -> Result<(), String> {
+    if !project_path.join("project.json").is_file() {
+        return Err("Not a project: project.json is missing".to_string());
+    }
+
+    let file = File::create(archive_path)
+        .map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let result = add_dir_to_archive(&mut zip, project_path, project_path, archive_path, options)
+        .and_then(|_| {
+            zip.finish()
+                .map(|_| ())
+                .map_err(|e| format!("Failed to finish archive: {}", e))
+        });
+
+    if result.is_err() {
+        let _ = fs::remove_file(archive_path);
+    }
+
+    result
+}
+
+fn add_dir_to_archive(
+    zip: &mut ZipWriter<File>,
+    root: &Path,
+    dir: &Path,
+    archive_path: &Path,
+    options: SimpleFileOptions,
+) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+
+        if path == archive_path {
+            continue;
+        }
+
+        let relative = path.strip_prefix(root)
+            .map_err(|_| "Invalid path inside project".to_string())?;
+        let name = relative.components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if is_non_portable(relative) {
+            continue;
+        }
+
+        if path.is_dir() {
+            zip.add_directory(format!("{}/", name), options)
+                .map_err(|e| format!("Failed to add directory to archive: {}", e))?;
+            add_dir_to_archive(zip, root, &path, archive_path, options)?;
+        } else {
+            zip.start_file(name, options)
+                .map_err(|e| format!("Failed to add file to archive: {}", e))?;
+            let mut source = File::open(&path)
+                .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+            io::copy(&mut source, zip)
+                .map_err(|e| format!("Failed to write {} to archive: {}", path.display(), e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads and validates `project.json` from an archive without extracting it.
+pub fn read_archive_metadata(archive_path: &Path) -> Result<ProjectMetadata, String> {
+    let mut archive = open_archive(archive_path)?;
+    let mut entry = archive.by_name("project.json")
+        .map_err(|_| "Invalid project archive: project.json is missing".to_string())?;
+
+    let mut content = String::new();
+    entry.read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read project.json from archive: {}", e))?;
+
+    ProjectMetadata::from_json_str(&content)
+        .map(|(metadata, _)| metadata)
+        .map_err(|e| format!("Invalid project archive: {}", e))
+}
+
+/// Extracts an archive into `dest`, rejecting entries that would escape it.
+/// Non-portable entries, e.g. a lock or build output packed by another
+/// tool, are left out.
+pub fn extract_project_archive(archive_path: &Path, dest: &Path) -> Result<(), String> {
+    let mut archive = open_archive(archive_path)?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)
+            .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+
+        let relative = entry.enclosed_name()
+            .filter(|p| p.components().all(|c| matches!(c, Component::Normal(_))))
+            .ok_or_else(|| format!("Invalid path in archive: {}", entry.name()))?;
+        if is_non_portable(&relative) {
+            continue;
+        }
+        let out_path: PathBuf = dest.join(relative);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&out_path)
+                .map_err(|e| format!("Failed to create directory: {}", e))?;
+            continue;
+        }
+
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+
+        let mut out = File::create(&out_path)
+            .map_err(|e| format!("Failed to create {}: {}", out_path.display(), e))?;
+        io::copy(&mut entry, &mut out)
+            .map_err(|e| format!("Failed to extract {}: {}", out_path.display(), e))?;
+        out.flush()
+            .map_err(|e| format!("Failed to extract {}: {}", out_path.display(), e))?;
+    }
+
+    Ok(())
+}
+
+fn open_archive(archive_path: &Path) -> Result<ZipArchive<File>, String> {
+    let file = File::open(archive_path)
+        .map_err(|e| format!("Failed to open archive: {}", e))?;
+    ZipArchive::new(file)
+        .map_err(|e| format!("Invalid project archive: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_lock::LOCK_FILE;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("project-archive-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn round_trips_projects_without_non_portable_entries() {
+        let dir = temp_dir("round-trip");
+        let project = dir.join("Game");
+        fs::create_dir_all(project.join("scenes")).unwrap();
+        fs::create_dir_all(project.join("build")).unwrap();
+        ProjectMetadata::new("Game").save(&project).unwrap();
+        fs::write(project.join("scenes/Main.json"), "{}").unwrap();
+        fs::write(project.join("build/index.html"), "").unwrap();
+        fs::write(project.join(LOCK_FILE), "").unwrap();
+
+        let archive = dir.join("Game.zip");
+        write_project_archive(&project, &archive).unwrap();
+        assert_eq!(read_archive_metadata(&archive).unwrap().name, "Game");
+
+        let imported = dir.join("Imported");
+        extract_project_archive(&archive, &imported).unwrap();
+        assert_eq!(fs::read_to_string(imported.join("scenes/Main.json")).unwrap(), "{}");
+        assert!(!imported.join("build").exists());
+        assert!(!imported.join(LOCK_FILE).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_entries_escaping_the_destination() {
+        let dir = temp_dir("zip-slip");
+        for name in ["../evil.txt", "scenes/../../evil.txt", "/tmp/evil.txt"] {
+            let archive = dir.join("evil.zip");
+            write_zip(&archive, &[("project.json", "{}"), (name, "evil")]);
+
+            let dest = dir.join("dest");
+            let error = extract_project_archive(&archive, &dest).unwrap_err();
+            assert!(error.starts_with("Invalid path in archive"), "{}: {}", name, error);
+            assert!(!dir.join("evil.txt").exists());
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_archives_that_are_not_projects() {
+        let dir = temp_dir("not-a-project");
+        assert!(write_project_archive(&dir, &dir.join("out.zip")).is_err());
+        assert!(!dir.join("out.zip").exists());
+
+        let archive = dir.join("other.zip");
+        write_zip(&archive, &[("readme.txt", "hello")]);
+        assert!(read_archive_metadata(&archive).unwrap_err().contains("project.json is missing"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,497 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::atomic_write::write_atomic;
+use crate::project_lock::ensure_unlocked;
+use crate::project_manager::{empty_scene_json, missing_gitignore_entries, write_gitignore, GITIGNORE_FILE};
+use crate::project_metadata::{ProjectMetadata, SceneConfig, PROJECT_FILE};
+use crate::project_metadata_store::ProjectMetadataStore;
+use crate::scene::SceneDocument;
+use crate::scene_store;
+
+/// Directories every project is expected to have.
+const REQUIRED_DIRS: &[&str] = &["assets", "build", "scenes"];
+
+pub(crate) const ASSETS_FILE: &str = "assets.json";
+
+/// Model files are shown as folders in the asset browser; their contents are
+/// virtual and have no files on disk.
+const MODEL_EXTENSIONS: &[&str] = &["glb", "gltf", "fbx", "obj"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemKind {
+    MissingDirectory,
+    MissingProjectFile,
+    InvalidProjectFile,
+    /// project.json loads, but has e.g. a misspelt key.
+    ProjectFileWarning,
+    InvalidScene,
+    MissingSceneFile,
+    UnregisteredScene,
+    MissingDefaultScene,
+    InvalidAssetsFile,
+    MissingAssetFile,
+    UnlistedAssetFile,
+    IncompleteGitignore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectProblem {
+    pub kind: ProblemKind,
+    pub severity: Severity,
+    /// Path of the affected file or directory, relative to the project.
+    pub path: String,
+    pub message: String,
+    /// Whether `repair_project` can fix this automatically.
+    pub repairable: bool,
+}
+
+impl ProjectProblem {
+    fn new(kind: ProblemKind, path: impl Into<String>, message: impl Into<String>) -> Self {
+        use ProblemKind::*;
+
+        let severity = match kind {
+            ProjectFileWarning | UnregisteredScene | UnlistedAssetFile | IncompleteGitignore => Severity::Warning,
+            _ => Severity::Error,
+        };
+        let repairable = matches!(
+            kind,
+            MissingDirectory | MissingProjectFile | MissingSceneFile | UnregisteredScene
+                | MissingDefaultScene | MissingAssetFile | IncompleteGitignore
+        );
+
+        ProjectProblem {
+            kind,
+            severity,
+            path: path.into(),
+            message: message.into(),
+            repairable,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepairReport {
+    pub repaired: Vec<ProjectProblem>,
+    pub remaining: Vec<ProjectProblem>,
+}
+
+/// Inspects a project and lists everything that looks broken. Nothing is
+/// modified.
+pub fn check_project(metadata_store: &ProjectMetadataStore, project_path: &Path) -> Result<Vec<ProjectProblem>, String> {
+    if !project_path.is_dir() {
+        return Err("Project does not exist".to_string());
+    }
+
+    let mut problems = Vec::new();
+
+    for dir in REQUIRED_DIRS {
+        if !project_path.join(dir).is_dir() {
+            problems.push(ProjectProblem::new(
+                ProblemKind::MissingDirectory,
+                *dir,
+                format!("The {}/ directory is missing", dir),
+            ));
+        }
+    }
+
+    let metadata = match metadata_store.load_optional(project_path) {
+        Ok(Some(metadata)) => {
+            for warning in metadata.warnings() {
+                problems.push(ProjectProblem::new(ProblemKind::ProjectFileWarning, PROJECT_FILE, warning));
+            }
+            Some(metadata)
+        }
+        Ok(None) => {
+            problems.push(ProjectProblem::new(
+                ProblemKind::MissingProjectFile,
+                PROJECT_FILE,
+                "project.json is missing",
+            ));
+            None
+        }
+        Err(e) => {
+            problems.push(ProjectProblem::new(ProblemKind::InvalidProjectFile, PROJECT_FILE, e));
+            None
+        }
+    };
+
+    let scene_files = scene_store::list_scene_names(project_path);
+    for name in &scene_files {
+        if let Err(e) = parse_scene(project_path, name) {
+            problems.push(ProjectProblem::new(
+                ProblemKind::InvalidScene,
+                format!("scenes/{}", name),
+                format!("Scene {} is invalid: {}", name, e),
+            ));
+        }
+    }
+
+    if let Some(metadata) = &metadata {
+        for name in metadata.scenes.keys() {
+            if !scene_files.contains(name) {
+                problems.push(ProjectProblem::new(
+                    ProblemKind::MissingSceneFile,
+                    format!("scenes/{}", name),
+                    format!("project.json lists scene {} but the file does not exist", name),
+                ));
+            }
+        }
+
+        for name in &scene_files {
+            if !metadata.scenes.contains_key(name) {
+                problems.push(ProjectProblem::new(
+                    ProblemKind::UnregisteredScene,
+                    format!("scenes/{}", name),
+                    format!("Scene {} is not listed in project.json", name),
+                ));
+            }
+        }
+
+        if !scene_files.contains(&metadata.default_scene) {
+            problems.push(ProjectProblem::new(
+                ProblemKind::MissingDefaultScene,
+                format!("scenes/{}", metadata.default_scene),
+                format!("The default scene {} does not exist", metadata.default_scene),
+            ));
+        }
+    }
+
+    check_assets(project_path, &mut problems);
+
+    let missing_ignores = missing_gitignore_entries(project_path)?;
+    if !missing_ignores.is_empty() {
+        problems.push(ProjectProblem::new(
+            ProblemKind::IncompleteGitignore,
+            GITIGNORE_FILE,
+            format!("{} does not ignore local editor files: {}", GITIGNORE_FILE, missing_ignores.join(", ")),
+        ));
+    }
+
+    Ok(problems)
+}
+
+/// Fixes the problems that can be repaired without losing data, then checks
+/// the project again.
+///
+/// Missing directories are created, a missing project.json is recreated,
+/// scene entries are synced with the files in `scenes/`, a missing default
+/// scene is replaced by an existing scene (or a new empty one),
+/// assets.json entries whose files are gone are dropped, and `.gitignore` is
+/// made to cover the local editor files.
+pub fn repair_project(metadata_store: &ProjectMetadataStore, project_path: &Path) -> Result<RepairReport, String> {
+    ensure_unlocked(project_path)?;
+
+    let problems = check_project(metadata_store, project_path)?;
+    if !problems.iter().any(|p| p.repairable) {
+        return Ok(RepairReport {
+            repaired: Vec::new(),
+            remaining: problems,
+        });
+    }
+    let repairable: Vec<_> = problems.into_iter().filter(|p| p.repairable).collect();
+
+    // Only what has findings is written, so e.g. fixing `.gitignore` leaves
+    // project.json and its `modified` time alone.
+    let has_kind = |kind: ProblemKind| repairable.iter().any(|p| p.kind == kind);
+
+    if has_kind(ProblemKind::MissingDirectory) {
+        for dir in REQUIRED_DIRS {
+            fs::create_dir_all(project_path.join(dir))
+                .map_err(|e| format!("Failed to create {} directory: {}", dir, e))?;
+        }
+    }
+
+    if has_kind(ProblemKind::MissingProjectFile) {
+        let name = project_path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Project");
+        let mut metadata = ProjectMetadata::new(name);
+        metadata.scenes.clear();
+        metadata_store.save(project_path, metadata)?;
+    }
+
+    let scenes_out_of_sync = [
+        ProblemKind::MissingProjectFile,
+        ProblemKind::MissingSceneFile,
+        ProblemKind::UnregisteredScene,
+        ProblemKind::MissingDefaultScene,
+    ].into_iter().any(has_kind);
+    if scenes_out_of_sync && metadata_store.load(project_path).is_ok() {
+        metadata_store.update(project_path, |metadata| {
+            let scene_files = scene_store::list_scene_names(project_path);
+
+            metadata.scenes.retain(|name, _| scene_files.contains(name));
+            for name in &scene_files {
+                metadata.scenes.entry(name.clone()).or_insert_with(SceneConfig::default);
+            }
+
+            if !scene_files.contains(&metadata.default_scene) {
+                let replacement = scene_files.iter()
+                    .find(|name| parse_scene(project_path, name).is_ok());
+                match replacement {
+                    Some(name) => metadata.default_scene = name.clone(),
+                    None => {
+                        let content = serde_json::to_string_pretty(&empty_scene_json())
+                            .map_err(|e| format!("Failed to serialize scene: {}", e))?;
+                        scene_store::write_scene_for(metadata, project_path, &metadata.default_scene, &content)
+                            .map_err(|e| format!("Failed to write default scene: {}", e))?;
+                        metadata.scenes.insert(metadata.default_scene.clone(), SceneConfig::default());
+                    }
+                }
+            }
+
+            Ok(())
+        })?;
+    }
+
+    if has_kind(ProblemKind::MissingAssetFile) {
+        remove_missing_asset_entries(project_path)?;
+    }
+
+    if has_kind(ProblemKind::IncompleteGitignore) {
+        write_gitignore(project_path)?;
+    }
+
+    let remaining = check_project(metadata_store, project_path)?;
+    let repaired = repairable.into_iter()
+        .filter(|fixed| !remaining.iter().any(|p| p.kind == fixed.kind && p.path == fixed.path))
+        .collect();
+
+    Ok(RepairReport { repaired, remaining })
+}
+
+fn parse_scene(project_path: &Path, name: &str) -> Result<SceneDocument, String> {
+    SceneDocument::from_json_str(&scene_store::read_scene(project_path, name)?)
+}
+
+fn check_assets(project_path: &Path, problems: &mut Vec<ProjectProblem>) {
+    let assets_dir = project_path.join("assets");
+    let assets_file = assets_dir.join(ASSETS_FILE);
+
+    let tree = match read_assets_tree(&assets_file) {
+        Ok(Some(tree)) => tree,
+        Ok(None) => return,
+        Err(e) => {
+            problems.push(ProjectProblem::new(
+                ProblemKind::InvalidAssetsFile,
+                format!("assets/{}", ASSETS_FILE),
+                e,
+            ));
+            return;
+        }
+    };
+
+    let mut listed = BTreeSet::new();
+    collect_asset_files(&tree, &mut listed);
+
+    for path in &listed {
+        if !assets_dir.join(path).is_file() {
+            problems.push(ProjectProblem::new(
+                ProblemKind::MissingAssetFile,
+                format!("assets/{}", path),
+                format!("assets.json lists {} but the file does not exist", path),
+            ));
+        }
+    }
+
+    let mut on_disk = Vec::new();
+    collect_disk_files(&assets_dir, "", &mut on_disk);
+    for path in on_disk {
+        if path != ASSETS_FILE && !listed.contains(&path) && !is_compiled_script(&assets_dir, &path) {
+            problems.push(ProjectProblem::new(
+                ProblemKind::UnlistedAssetFile,
+                format!("assets/{}", path),
+                format!("{} is not listed in assets.json", path),
+            ));
+        }
+    }
+}
+
+/// Reads assets.json. `None` means there is no asset list yet.
+pub(crate) fn read_assets_tree(assets_file: &Path) -> Result<Option<Value>, String> {
+    let content = match fs::read_to_string(assets_file) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read assets.json: {}", e)),
+    };
+
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("assets.json could not be parsed: {}", e))
+}
+
+fn is_model_folder(folder: &Value) -> bool {
+    folder.get("name")
+        .and_then(|n| n.as_str())
+        .and_then(|n| Path::new(n).extension())
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| MODEL_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Asset file entries that should exist on disk, as paths relative to
+/// `assets/`. Virtual entries inside models are skipped.
+fn asset_file_entries(folder: &Value) -> impl Iterator<Item = (usize, String)> + '_ {
+    folder.get("files")
+        .and_then(|f| f.as_array())
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, file)| file.get("modelPath").map_or(true, |m| m.is_null()))
+        .filter_map(|(index, file)| {
+            let path = file.get("path")?.as_str()?.trim_start_matches('/');
+            (!path.is_empty()).then(|| (index, path.to_string()))
+        })
+}
+
+fn collect_asset_files(folder: &Value, listed: &mut BTreeSet<String>) {
+    if is_model_folder(folder) {
+        return;
+    }
+
+    listed.extend(asset_file_entries(folder).map(|(_, path)| path));
+
+    if let Some(children) = folder.get("children").and_then(|c| c.as_array()) {
+        for child in children {
+            collect_asset_files(child, listed);
+        }
+    }
+}
+
+fn collect_disk_files(dir: &Path, prefix: &str, files: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let relative = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+        let path = entry.path();
+
+        if path.is_dir() {
+            collect_disk_files(&path, &relative, files);
+        } else {
+            files.push(relative);
+        }
+    }
+}
+
+/// `.js` files next to a `.ts` source of the same name are compiler output
+/// and are not listed in assets.json.
+fn is_compiled_script(assets_dir: &Path, path: &str) -> bool {
+    let path = assets_dir.join(path);
+    let is_js = path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e == "js" || e == "jsx");
+
+    is_js && ["ts", "tsx"].iter().any(|ext| path.with_extension(ext).is_file())
+}
+
+fn remove_missing_asset_entries(project_path: &Path) -> Result<(), String> {
+    let assets_dir = project_path.join("assets");
+    let assets_file = assets_dir.join(ASSETS_FILE);
+    let Some(mut tree) = read_assets_tree(&assets_file)? else {
+        return Ok(());
+    };
+
+    prune_missing_files(&mut tree, &assets_dir);
+
+    let content = serde_json::to_string_pretty(&tree)
+        .map_err(|e| format!("Failed to serialize assets.json: {}", e))?;
+    write_atomic(&assets_file, content)
+        .map_err(|e| format!("Failed to write assets.json: {}", e))
+}
+
+fn prune_missing_files(folder: &mut Value, assets_dir: &Path) {
+    if is_model_folder(folder) {
+        return;
+    }
+
+    let missing: Vec<usize> = asset_file_entries(folder)
+        .filter(|(_, path)| !assets_dir.join(path).is_file())
+        .map(|(index, _)| index)
+        .collect();
+
+    if let Some(files) = folder.get_mut("files").and_then(|f| f.as_array_mut()) {
+        for index in missing.into_iter().rev() {
+            files.remove(index);
+        }
+    }
+
+    if let Some(children) = folder.get_mut("children").and_then(|c| c.as_array_mut()) {
+        for child in children {
+            prune_missing_files(child, assets_dir);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(name: &str) -> std::path::PathBuf {
+        let project = std::env::temp_dir()
+            .join(format!("project-check-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&project);
+        for dir in REQUIRED_DIRS {
+            fs::create_dir_all(project.join(dir)).unwrap();
+        }
+
+        let metadata = ProjectMetadata::new("Game");
+        let content = serde_json::to_string_pretty(&empty_scene_json()).unwrap();
+        scene_store::write_scene_for(&metadata, &project, &metadata.default_scene, &content).unwrap();
+        metadata.save(&project).unwrap();
+        project
+    }
+
+    #[test]
+    fn repairs_only_what_has_findings() {
+        let project = project("gitignore");
+        let metadata_store = ProjectMetadataStore::new();
+        let before = fs::read(project.join(PROJECT_FILE)).unwrap();
+
+        let report = repair_project(&metadata_store, &project).unwrap();
+        let repaired: Vec<_> = report.repaired.iter().map(|p| p.kind).collect();
+        assert_eq!(repaired, vec![ProblemKind::IncompleteGitignore]);
+        assert!(report.remaining.is_empty());
+        assert_eq!(fs::read(project.join(PROJECT_FILE)).unwrap(), before);
+
+        fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    fn syncs_scene_entries_with_the_files() {
+        let project = project("scenes");
+        let metadata_store = ProjectMetadataStore::new();
+        write_gitignore(&project).unwrap();
+        fs::copy(project.join("scenes/Main.json"), project.join("scenes/Level.json")).unwrap();
+        fs::remove_dir_all(project.join("build")).unwrap();
+
+        let problems: Vec<_> = check_project(&metadata_store, &project).unwrap()
+            .into_iter().map(|p| p.kind).collect();
+        assert_eq!(problems, vec![ProblemKind::MissingDirectory, ProblemKind::UnregisteredScene]);
+
+        let report = repair_project(&metadata_store, &project).unwrap();
+        assert_eq!(report.repaired.len(), 2);
+        assert!(report.remaining.is_empty());
+        assert!(project.join("build").is_dir());
+        assert!(metadata_store.load(&project).unwrap().scenes.contains_key("Level.json"));
+
+        fs::remove_dir_all(&project).unwrap();
+    }
+}
@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::atomic_write::write_atomic;
+
+/// Key in editor.json under which project locations are stored. The key is
+/// owned by the backend: `write_editor_config` keeps the on-disk value.
+pub const EDITOR_CONFIG_KEY: &str = "projectLocations";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LocationKind {
+    /// A directory whose subdirectories are projects.
+    Root,
+    /// A single project directory.
+    Project,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LocationInfo {
+    pub path: String,
+    pub kind: LocationKind,
+    pub exists: bool,
+}
+
+/// Project roots and individually registered projects that live outside the
+/// default projects directory.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ProjectLocations {
+    #[serde(default)]
+    pub roots: Vec<String>,
+    #[serde(default)]
+    pub projects: Vec<String>,
+    #[serde(skip)]
+    config_path: Option<PathBuf>,
+}
+
+impl ProjectLocations {
+    /// Reads the locations from an editor.json file. A missing file or key
+    /// yields an empty list.
+    pub fn load(config_path: &Path) -> Result<Self, String> {
+        let config = read_editor_config(config_path)?;
+
+        let mut locations = match config.get(EDITOR_CONFIG_KEY) {
+            Some(value) => serde_json::from_value::<ProjectLocations>(value.clone())
+                .map_err(|e| format!("Invalid {} in editor config: {}", EDITOR_CONFIG_KEY, e))?,
+            None => ProjectLocations::default(),
+        };
+        locations.config_path = Some(config_path.to_path_buf());
+
+        Ok(locations)
+    }
+
+    /// Writes the locations back into editor.json, leaving other keys as
+    /// they are.
+    pub fn save(&self) -> Result<(), String> {
+        let config_path = self.config_path.as_ref()
+            .ok_or("Project locations have no config file")?;
+
+        let mut config = read_editor_config(config_path)?;
+        config.insert(
+            EDITOR_CONFIG_KEY.to_string(),
+            serde_json::to_value(self)
+                .map_err(|e| format!("Failed to serialize project locations: {}", e))?,
+        );
+
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+
+        let content = serde_json::to_string_pretty(&config)
+            .map_err(|e| format!("Failed to serialize editor config: {}", e))?;
+        write_atomic(config_path, content)
+            .map_err(|e| format!("Failed to write editor config: {}", e))
+    }
+
+    pub fn list(&self) -> Vec<LocationInfo> {
+        let roots = self.roots.iter().map(|p| (p, LocationKind::Root));
+        let projects = self.projects.iter().map(|p| (p, LocationKind::Project));
+
+        roots.chain(projects)
+            .map(|(path, kind)| LocationInfo {
+                path: path.clone(),
+                kind,
+                exists: Path::new(path).is_dir(),
+            })
+            .collect()
+    }
+
+    pub fn add(&mut self, path: &str, kind: LocationKind) -> Result<String, String> {
+        let path = Path::new(path);
+        if !path.is_dir() {
+            return Err(format!("Directory does not exist: {}", path.display()));
+        }
+        if kind == LocationKind::Project && !path.join("project.json").is_file() {
+            return Err("Not a project: project.json is missing".to_string());
+        }
+
+        let path = canonical_string(path);
+        let list = match kind {
+            LocationKind::Root => &mut self.roots,
+            LocationKind::Project => &mut self.projects,
+        };
+        if !list.contains(&path) {
+            list.push(path.clone());
+        }
+
+        Ok(path)
+    }
+
+    /// Removes a root or registered project. Returns `false` if the path was
+    /// not registered.
+    pub fn remove(&mut self, path: &str) -> bool {
+        let canonical = canonical_string(Path::new(path));
+        let before = self.roots.len() + self.projects.len();
+        self.roots.retain(|p| p != path && *p != canonical);
+        self.projects.retain(|p| p != path && *p != canonical);
+        before != self.roots.len() + self.projects.len()
+    }
+
+    /// Replaces a registered project path, e.g. after the project directory
+    /// was renamed.
+    pub fn replace_project(&mut self, old_path: &Path, new_path: &Path) -> bool {
+        let old_canonical = canonical_string(old_path);
+        let old_string = old_path.to_string_lossy();
+        match self.projects.iter_mut().find(|p| **p == old_string || **p == old_canonical) {
+            Some(entry) => {
+                *entry = canonical_string(new_path);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains_project(&self, path: &Path) -> bool {
+        let canonical = canonical_string(path);
+        let path_string = path.to_string_lossy();
+        self.projects.iter().any(|p| *p == path_string || *p == canonical)
+    }
+
+    /// Whether `path` lies inside one of the roots. Compared after
+    /// canonicalization so `..` components can't escape a root.
+    pub fn is_in_root(&self, path: &Path) -> bool {
+        let canonical = PathBuf::from(canonical_string(path));
+        self.roots.iter().any(|root| canonical.starts_with(root))
+    }
+}
+
+/// Returns the canonical form of `path` when it exists, so that the same
+/// project registered through different spellings is only listed once.
+pub fn canonical_string(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+fn read_editor_config(config_path: &Path) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let content = match fs::read_to_string(config_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(serde_json::Map::new()),
+        Err(e) => return Err(format!("Failed to read editor config: {}", e)),
+    };
+
+    if content.trim().is_empty() {
+        return Ok(serde_json::Map::new());
+    }
+
+    match serde_json::from_str::<serde_json::Value>(&content) {
+        Ok(serde_json::Value::Object(map)) => Ok(map),
+        Ok(_) => Err("Invalid editor config: expected an object".to_string()),
+        Err(e) => Err(format!("Invalid editor config: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("project-locations-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn registers_each_location_once() {
+        let dir = temp_dir("register");
+        let root = dir.join("root");
+        let project = dir.join("Game");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&project).unwrap();
+        let mut locations = ProjectLocations::default();
+
+        assert!(locations.add(&project.to_string_lossy(), LocationKind::Project).is_err());
+        fs::write(project.join("project.json"), "{}").unwrap();
+        assert!(locations.add(&dir.join("missing").to_string_lossy(), LocationKind::Root).is_err());
+
+        locations.add(&root.to_string_lossy(), LocationKind::Root).unwrap();
+        locations.add(&dir.join("root/../root").to_string_lossy(), LocationKind::Root).unwrap();
+        locations.add(&project.to_string_lossy(), LocationKind::Project).unwrap();
+        assert_eq!(locations.roots, [root.to_string_lossy()]);
+        assert!(locations.contains_project(&dir.join("./Game")));
+
+        assert!(locations.is_in_root(&root.join("Level")));
+        assert!(!locations.is_in_root(&root.join("../Game")));
+
+        let renamed = dir.join("Sequel");
+        assert!(locations.replace_project(&project, &renamed));
+        assert!(locations.contains_project(&renamed));
+
+        assert!(locations.remove(&root.to_string_lossy()));
+        assert!(!locations.remove(&root.to_string_lossy()));
+        assert!(locations.roots.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_the_rest_of_the_editor_config() {
+        let dir = temp_dir("config");
+        let config_path = dir.join("editor.json");
+        fs::write(&config_path, r#"{ "theme": "dark" }"#).unwrap();
+
+        let mut locations = ProjectLocations::load(&config_path).unwrap();
+        assert!(locations.list().is_empty());
+        locations.add(&dir.to_string_lossy(), LocationKind::Root).unwrap();
+        locations.save().unwrap();
+
+        let config: serde_json::Value = serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(config["theme"], "dark");
+        let list = ProjectLocations::load(&config_path).unwrap().list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].kind, LocationKind::Root);
+        assert!(list[0].exists);
+
+        fs::write(&config_path, r#"{ "projectLocations": { "roots": 1 } }"#).unwrap();
+        assert!(ProjectLocations::load(&config_path).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
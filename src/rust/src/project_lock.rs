@@ -0,0 +1,413 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::atomic_write::write_atomic;
+use crate::project_metadata::now_secs;
+
+/// Lock file in the project root, present while an editor has the project
+/// open for writing.
+pub const LOCK_FILE: &str = ".lock";
+
+/// Prefix of the error returned when a project is locked by someone else. It
+/// is followed by the holder's `LockInfo` as JSON.
+pub const LOCKED_ERROR_PREFIX: &str = "PROJECT_LOCKED: ";
+
+/// Prefix of the error returned by mutating commands while the project is
+/// open in read-only mode.
+pub const READ_ONLY_ERROR_PREFIX: &str = "READ_ONLY: ";
+
+/// How often the holder of a lock marks it as still in use.
+const LOCK_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A lock not refreshed for this long is stale wherever it was taken, e.g.
+/// one left by a crash on another machine or committed by accident.
+const LOCK_EXPIRY_SECS: u64 = 10 * 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenMode {
+    /// Take the lock, failing if another instance holds it.
+    #[default]
+    ReadWrite,
+    /// Open without taking the lock. Nothing is written to the project.
+    ReadOnly,
+    /// Take the lock even if another instance holds it.
+    TakeOver,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockInfo {
+    pub pid: u32,
+    pub hostname: String,
+    pub acquired_at: u64,
+    /// Last time the holder marked the lock as in use. Missing in locks
+    /// written by older versions.
+    #[serde(default)]
+    pub refreshed_at: u64,
+    /// Tells apart locks taken by the same process within the same second,
+    /// e.g. when a project is reopened. Missing in locks written by older
+    /// versions.
+    #[serde(default)]
+    pub acquisition: u64,
+}
+
+impl LockInfo {
+    fn current() -> Self {
+        static ACQUISITIONS: AtomicU64 = AtomicU64::new(1);
+
+        let now = now_secs();
+        LockInfo {
+            pid: std::process::id(),
+            hostname: hostname(),
+            acquired_at: now,
+            refreshed_at: now,
+            acquisition: ACQUISITIONS.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Whether the lock was taken by this process.
+    pub fn is_ours(&self) -> bool {
+        self.pid == std::process::id() && self.hostname == hostname()
+    }
+
+    /// Whether both describe the same acquisition of a lock, however often
+    /// it was refreshed since.
+    fn same_holder(&self, other: &LockInfo) -> bool {
+        self.pid == other.pid
+            && self.hostname == other.hostname
+            && self.acquired_at == other.acquired_at
+            && self.acquisition == other.acquisition
+    }
+
+    /// A lock is stale when it hasn't been refreshed for `LOCK_EXPIRY_SECS`,
+    /// or when it was taken on this machine by a process that is no longer
+    /// running.
+    pub fn is_stale(&self) -> bool {
+        let last_seen = self.refreshed_at.max(self.acquired_at);
+        if now_secs().saturating_sub(last_seen) > LOCK_EXPIRY_SECS {
+            return true;
+        }
+        self.hostname == hostname() && !process_alive(self.pid)
+    }
+
+    fn locked_error(&self) -> String {
+        format!(
+            "{}{}",
+            LOCKED_ERROR_PREFIX,
+            serde_json::to_string(self).unwrap_or_default()
+        )
+    }
+}
+
+/// A held project lock. It is refreshed in the background while held, and
+/// the lock file is removed when this is dropped.
+#[derive(Debug)]
+pub struct ProjectLock {
+    lock_path: PathBuf,
+    info: LockInfo,
+    refresh: Option<Refresh>,
+}
+
+#[derive(Debug)]
+struct Refresh {
+    /// Dropping this stops the thread.
+    stop: mpsc::Sender<()>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl ProjectLock {
+    /// Takes the lock on a project. Stale locks and locks held by this
+    /// process are replaced; a live lock held elsewhere is only replaced
+    /// with `take_over`.
+    pub fn acquire(project_path: &Path, take_over: bool) -> Result<Self, String> {
+        let lock_path = project_path.join(LOCK_FILE);
+        let info = LockInfo::current();
+        let content = serde_json::to_string_pretty(&info)
+            .map_err(|e| format!("Failed to serialize lock: {}", e))?;
+
+        // A second attempt is made after clearing a lock we're allowed to replace.
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+                Ok(mut file) => {
+                    file.write_all(content.as_bytes())
+                        .map_err(|e| format!("Failed to write lock file: {}", e))?;
+                    let refresh = Some(spawn_refresh(lock_path.clone(), info.clone()));
+                    return Ok(ProjectLock { lock_path, info, refresh });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if let Some(holder) = read_lock(project_path) {
+                        if !take_over && !holder.is_ours() && !holder.is_stale() {
+                            return Err(holder.locked_error());
+                        }
+                    }
+                    fs::remove_file(&lock_path)
+                        .map_err(|e| format!("Failed to remove old lock file: {}", e))?;
+                }
+                Err(e) => return Err(format!("Failed to create lock file: {}", e)),
+            }
+        }
+
+        Err("Failed to lock project: lock file keeps reappearing".to_string())
+    }
+}
+
+impl Drop for ProjectLock {
+    fn drop(&mut self) {
+        // Stop refreshing first, so the file isn't written again after it is
+        // removed.
+        if let Some(refresh) = self.refresh.take() {
+            drop(refresh.stop);
+            let _ = refresh.thread.join();
+        }
+
+        // Leave the file alone if another instance has taken the project over.
+        if read_lock_file(&self.lock_path).is_some_and(|holder| holder.same_holder(&self.info)) {
+            let _ = fs::remove_file(&self.lock_path);
+        }
+    }
+}
+
+/// Rewrites the lock with a new `refreshed_at` every
+/// `LOCK_REFRESH_INTERVAL` until stopped, or until another instance has
+/// taken the project over.
+fn spawn_refresh(lock_path: PathBuf, info: LockInfo) -> Refresh {
+    let (stop, stopped) = mpsc::channel::<()>();
+
+    let thread = std::thread::spawn(move || {
+        let mut info = info;
+        while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(LOCK_REFRESH_INTERVAL) {
+            if !read_lock_file(&lock_path).is_some_and(|holder| holder.same_holder(&info)) {
+                break;
+            }
+            info.refreshed_at = now_secs();
+            let Ok(content) = serde_json::to_string_pretty(&info) else {
+                break;
+            };
+            if let Err(e) = write_atomic(&lock_path, content) {
+                log::warn!("[Editor] Failed to refresh project lock: {}", e);
+            }
+        }
+    });
+
+    Refresh { stop, thread }
+}
+
+/// Returns the current lock holder of a project, if any. An unreadable lock
+/// file is treated as no lock.
+pub fn read_lock(project_path: &Path) -> Option<LockInfo> {
+    read_lock_file(&project_path.join(LOCK_FILE))
+}
+
+fn read_lock_file(lock_path: &Path) -> Option<LockInfo> {
+    let content = fs::read_to_string(lock_path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Fails with a `PROJECT_LOCKED` error if another live instance holds the
+/// project's lock.
+pub fn ensure_unlocked(project_path: &Path) -> Result<(), String> {
+    match read_lock(project_path) {
+        Some(holder) if !holder.is_ours() && !holder.is_stale() => Err(holder.locked_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Fails if the project at `project_path` is `read_only_project`, the one
+/// this editor has open in read-only mode, or if another live instance holds
+/// its lock.
+pub fn ensure_writable(read_only_project: Option<&Path>, project_path: &Path) -> Result<(), String> {
+    if let Some(read_only_project) = read_only_project.filter(|p| same_path(p, project_path)) {
+        return Err(format!(
+            "{}Project is open in read-only mode: {}",
+            READ_ONLY_ERROR_PREFIX,
+            read_only_project.display()
+        ));
+    }
+    ensure_unlocked(project_path)
+}
+
+/// Whether both paths name the same file, however they are spelled.
+fn same_path(a: &Path, b: &Path) -> bool {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    canonical(a) == canonical(b)
+}
+
+/// Removes a project's lock whoever holds it. This is the way out when the
+/// holder is gone but can't be recognized as such, e.g. an editor on another
+/// machine that crashed moments ago. Fails if this process holds the lock.
+pub fn break_lock(project_path: &Path) -> Result<(), String> {
+    if read_lock(project_path).is_some_and(|holder| holder.is_ours()) {
+        return Err("The project is open in this editor".to_string());
+    }
+
+    match fs::remove_file(project_path.join(LOCK_FILE)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove lock file: {}", e)),
+    }
+}
+
+/// Name of this machine, looked up once per process.
+fn hostname() -> String {
+    static HOSTNAME: OnceLock<String> = OnceLock::new();
+
+    HOSTNAME.get_or_init(|| {
+        std::env::var("COMPUTERNAME")
+            .ok()
+            .or_else(|| fs::read_to_string("/etc/hostname").ok())
+            .or_else(|| {
+                Command::new("hostname")
+                    .output()
+                    .ok()
+                    .and_then(|output| String::from_utf8(output.stdout).ok())
+            })
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "unknown".to_string())
+    }).clone()
+}
+
+/// Whether a process with the given PID is running. If that can't be
+/// determined the process is assumed to be alive.
+fn process_alive(pid: u32) -> bool {
+    #[cfg(target_os = "windows")]
+    let result = Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+        .stderr(Stdio::null())
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)));
+
+    #[cfg(not(target_os = "windows"))]
+    let result = Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success());
+
+    result.unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("project-lock-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A live lock held by an editor on another machine.
+    fn foreign_lock(refreshed_at: u64) -> LockInfo {
+        LockInfo {
+            pid: 1,
+            hostname: format!("not-{}", hostname()),
+            acquired_at: refreshed_at,
+            refreshed_at,
+            acquisition: 0,
+        }
+    }
+
+    fn write_lock(project: &Path, info: &LockInfo) {
+        fs::write(project.join(LOCK_FILE), serde_json::to_string(info).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn holds_the_lock_until_dropped() {
+        let project = temp_project("hold");
+        let lock = ProjectLock::acquire(&project, false).unwrap();
+        let holder = read_lock(&project).unwrap();
+        assert!(holder.is_ours() && !holder.is_stale());
+        assert!(ensure_unlocked(&project).is_ok());
+        assert!(break_lock(&project).is_err());
+
+        // Reopening in this process replaces the lock; dropping the old one
+        // then leaves the new one alone.
+        let reopened = ProjectLock::acquire(&project, false).unwrap();
+        drop(lock);
+        assert!(project.join(LOCK_FILE).exists());
+        drop(reopened);
+        assert!(!project.join(LOCK_FILE).exists());
+
+        fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    fn respects_live_locks_held_elsewhere() {
+        let project = temp_project("foreign");
+        let foreign = foreign_lock(now_secs());
+        write_lock(&project, &foreign);
+
+        let error = ProjectLock::acquire(&project, false).unwrap_err();
+        let holder: LockInfo = serde_json::from_str(error.strip_prefix(LOCKED_ERROR_PREFIX).unwrap()).unwrap();
+        assert_eq!(holder, foreign);
+        assert!(ensure_unlocked(&project).unwrap_err().starts_with(LOCKED_ERROR_PREFIX));
+
+        // Taken over, the other editor's lock is no longer there to remove.
+        let lock = ProjectLock::acquire(&project, true).unwrap();
+        assert!(read_lock(&project).unwrap().is_ours());
+        write_lock(&project, &foreign);
+        drop(lock);
+        assert_eq!(read_lock(&project), Some(foreign));
+
+        break_lock(&project).unwrap();
+        assert_eq!(read_lock(&project), None);
+        assert!(break_lock(&project).is_ok());
+
+        fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    fn replaces_stale_locks() {
+        let project = temp_project("stale");
+        let expired = foreign_lock(now_secs() - LOCK_EXPIRY_SECS - 1);
+        assert!(expired.is_stale());
+        write_lock(&project, &expired);
+        assert!(ensure_unlocked(&project).is_ok());
+        drop(ProjectLock::acquire(&project, false).unwrap());
+
+        // Left by a process of this machine that is gone.
+        let mut exited = Command::new("hostname").stdout(Stdio::null()).spawn().unwrap();
+        exited.wait().unwrap();
+        let crashed = LockInfo { hostname: hostname(), pid: exited.id(), ..foreign_lock(now_secs()) };
+        assert!(crashed.is_stale());
+        write_lock(&project, &crashed);
+        drop(ProjectLock::acquire(&project, false).unwrap());
+
+        // Unreadable locks count as none.
+        fs::write(project.join(LOCK_FILE), "garbage").unwrap();
+        assert!(ensure_unlocked(&project).is_ok());
+        drop(ProjectLock::acquire(&project, false).unwrap());
+
+        fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    fn refuses_writes_to_a_project_open_read_only() {
+        let project = temp_project("read-only");
+        let other = temp_project("read-only-other");
+        fs::create_dir_all(project.join("scenes")).unwrap();
+
+        let error = ensure_writable(Some(&project), &project.join("scenes").join("..")).unwrap_err();
+        assert!(error.starts_with(READ_ONLY_ERROR_PREFIX));
+        // Only the project open read-only is, not every project.
+        assert!(ensure_writable(Some(&project), &other).is_ok());
+        assert!(ensure_writable(None, &project).is_ok());
+
+        write_lock(&other, &foreign_lock(now_secs()));
+        assert!(ensure_writable(Some(&project), &other).unwrap_err().starts_with(LOCKED_ERROR_PREFIX));
+
+        fs::remove_dir_all(&project).unwrap();
+        fs::remove_dir_all(&other).unwrap();
+    }
+}
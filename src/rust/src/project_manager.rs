@@ -1,17 +1,164 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::atomic_write::write_atomic;
+use crate::project_archive::{extract_project_archive, read_archive_metadata, write_project_archive};
+use crate::project_lock::{ensure_unlocked, LOCK_FILE};
+use crate::project_locations::{canonical_string, LocationKind, ProjectLocations};
+use crate::project_metadata::{now_secs, ProjectMetadata, PROJECT_FILE};
+use crate::project_metadata_store::ProjectMetadataStore;
+use crate::project_templates::{instantiate_template, TemplateVars};
+use crate::scene_history::HISTORY_DIR;
+use crate::scene_recovery::RECOVERY_DIR;
+use crate::undo_history::CACHE_DIR;
+use crate::scene_store;
+
+/// Project entries that are generated or machine specific and are not
+/// carried over when a project is exported, imported or duplicated. Paths
+/// are relative to the project root and `/`-separated.
+pub const NON_PORTABLE_ENTRIES: &[&str] = &[
+    "build",
+    "node_modules",
+    LOCK_FILE,
+    RECOVERY_DIR,
+    HISTORY_DIR,
+    CACHE_DIR,
+    scene_store::BACKUP_PATH,
+];
+
+/// Whether `relative`, a path relative to the project root, is one of the
+/// `NON_PORTABLE_ENTRIES` or lies inside one.
+pub fn is_non_portable(relative: &Path) -> bool {
+    NON_PORTABLE_ENTRIES.iter().any(|entry| relative.starts_with(entry))
+}
+
+pub(crate) const GITIGNORE_FILE: &str = ".gitignore";
+
+/// Image formats accepted as project thumbnails.
+const THUMBNAIL_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "gif"];
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProjectInfo {
     pub name: String,
     pub path: String,
     pub modified: u64,
+    #[serde(default)]
+    pub created: u64,
+    /// Absolute path of the thumbnail image, if the project has one.
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    #[serde(default)]
+    pub scene_count: usize,
+    #[serde(default)]
+    pub asset_count: usize,
+    /// Size of the project's own files in bytes. Generated and machine
+    /// specific entries (`NON_PORTABLE_ENTRIES`) are not counted.
+    #[serde(default)]
+    pub disk_size: u64,
+    #[serde(default)]
+    pub format_version: Option<u32>,
+    #[serde(default)]
+    pub engine_version: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Set when project.json exists but could not be parsed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Set for registered projects whose directory no longer exists.
+    #[serde(default)]
+    pub missing: bool,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectSortKey {
+    Name,
+    #[default]
+    Modified,
+    Created,
+    SceneCount,
+    AssetCount,
+    DiskSize,
+}
+
+/// Filtering and ordering for `list_projects`. Every field is optional; the
+/// default lists all projects, most recently modified first.
+#[derive(Debug, Default, Deserialize)]
+pub struct ProjectQuery {
+    /// Case-insensitive match against the project name and tags.
+    #[serde(default)]
+    pub search: Option<String>,
+    /// Only projects carrying all of these tags.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub sort_by: ProjectSortKey,
+    /// Sort ascending instead of descending.
+    #[serde(default)]
+    pub ascending: bool,
+}
+
+impl ProjectQuery {
+    fn matches(&self, project: &ProjectInfo) -> bool {
+        let has_tag = |tag: &str| project.tags.iter().any(|t| t.eq_ignore_ascii_case(tag));
+        if !self.tags.iter().all(|tag| has_tag(tag)) {
+            return false;
+        }
+
+        match self.search.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+            Some(search) => {
+                let search = search.to_lowercase();
+                project.name.to_lowercase().contains(&search)
+                    || project.tags.iter().any(|t| t.to_lowercase().contains(&search))
+            }
+            None => true,
+        }
+    }
+
+    fn apply(&self, projects: &mut Vec<ProjectInfo>) {
+        projects.retain(|p| self.matches(p));
+        projects.sort_by(|a, b| {
+            let ordering = match self.sort_by {
+                ProjectSortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                ProjectSortKey::Modified => a.modified.cmp(&b.modified),
+                ProjectSortKey::Created => a.created.cmp(&b.created),
+                ProjectSortKey::SceneCount => a.scene_count.cmp(&b.scene_count),
+                ProjectSortKey::AssetCount => a.asset_count.cmp(&b.asset_count),
+                ProjectSortKey::DiskSize => a.disk_size.cmp(&b.disk_size),
+            };
+            if self.ascending { ordering } else { ordering.reverse() }
+        });
+    }
+}
+
+/// A deleted project waiting in the trash.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrashedProject {
+    pub id: String,
+    pub name: String,
+    pub original_path: String,
+    pub deleted_at: u64,
+    /// Whether the project was individually registered before deletion, so
+    /// that restoring it registers it again.
+    #[serde(default)]
+    pub registered: bool,
+}
+
+/// Deleted projects are moved here, inside the projects directory or project
+/// root they were in, so that deleting is a rename on the same file system.
+/// Registered projects outside of both go to the projects directory's trash,
+/// which may mean copying them over. Each entry is a `<id>/` directory plus
+/// a `<id>.json` manifest.
+const TRASH_DIR: &str = ".trash";
+
 pub struct ProjectManager {
     projects_dir: PathBuf,
+    locations: ProjectLocations,
+    metadata: Arc<ProjectMetadataStore>,
 }
 
 impl ProjectManager {
@@ -26,7 +173,24 @@ impl ProjectManager {
         } else {
         }
 
-        Ok(ProjectManager { projects_dir })
+        Ok(ProjectManager {
+            projects_dir,
+            locations: ProjectLocations::default(),
+            metadata: Arc::default(),
+        })
+    }
+
+    /// Also manage the project roots and registered projects in `locations`.
+    pub fn with_locations(mut self, locations: ProjectLocations) -> Self {
+        self.locations = locations;
+        self
+    }
+
+    /// Read and update project.json through `metadata`, shared with the
+    /// editor's commands.
+    pub fn with_metadata_store(mut self, metadata: Arc<ProjectMetadataStore>) -> Self {
+        self.metadata = metadata;
+        self
     }
 
     fn get_projects_directory() -> Result<PathBuf, String> {
@@ -37,58 +201,141 @@ impl ProjectManager {
             }
         }
         
-        let exe_path = std::env::current_exe()
-            .map_err(|e| format!("Failed to get current executable path: {}", e))?;
-        
-        let base_dir = if cfg!(debug_assertions) {
-            // In debug mode, we're running from src/rust, so go up to project root
-            let current_dir = std::env::current_dir()
-                .map_err(|e| format!("Failed to get current directory: {}", e))?;
-            if current_dir.ends_with("rust") {
-                current_dir.parent()
-                    .and_then(|p| p.parent())
-                    .ok_or("Failed to get project root directory")?
-                    .to_path_buf()
-            } else {
-                current_dir
-            }
-        } else {
-            exe_path.parent()
-                .ok_or("Failed to get executable parent directory")?
-                .to_path_buf()
-        };
-        
-        let projects_dir = base_dir.join("projects");
+        let projects_dir = get_base_directory()?.join("projects");
         
         Ok(projects_dir)
     }
 
-    pub fn list_projects(&self) -> Result<Vec<ProjectInfo>, String> {
+    /// Lists the projects in the projects directory, in every project root
+    /// and the individually registered ones, filtered and sorted by `query`.
+    /// Registered projects that no longer exist are included with `missing`
+    /// set.
+    pub fn list_projects(&self, query: &ProjectQuery) -> Result<Vec<ProjectInfo>, String> {
         let mut projects = Vec::new();
+        let mut seen = HashSet::new();
 
-        if !self.projects_dir.exists() {
-            return Ok(projects);
+        if self.projects_dir.exists() {
+            for path in Self::project_dirs(&self.projects_dir, false)? {
+                if seen.insert(canonical_string(&path)) {
+                    projects.push(self.get_project_info(&path)?);
+                }
+            }
+        }
+
+        for root in &self.locations.roots {
+            let root = Path::new(root);
+            if !root.is_dir() {
+                continue;
+            }
+            for path in Self::project_dirs(root, true)? {
+                if seen.insert(canonical_string(&path)) {
+                    projects.push(self.get_project_info(&path)?);
+                }
+            }
         }
 
-        let entries = fs::read_dir(&self.projects_dir)
-            .map_err(|e| format!("Failed to read projects directory: {}", e))?;
+        for registered in &self.locations.projects {
+            let path = Path::new(registered);
+            if !seen.insert(canonical_string(path)) {
+                continue;
+            }
 
+            if path.is_dir() {
+                projects.push(self.get_project_info(path)?);
+            } else {
+                projects.push(ProjectInfo {
+                    name: path.file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_else(|| registered.clone()),
+                    path: registered.clone(),
+                    modified: 0,
+                    created: 0,
+                    thumbnail: None,
+                    scene_count: 0,
+                    asset_count: 0,
+                    disk_size: 0,
+                    format_version: None,
+                    engine_version: None,
+                    tags: Vec::new(),
+                    error: None,
+                    missing: true,
+                });
+            }
+        }
+
+        query.apply(&mut projects);
+
+        Ok(projects)
+    }
+
+    /// Non-hidden subdirectories of `dir`. With `require_metadata` only
+    /// directories containing a project.json are returned.
+    fn project_dirs(dir: &Path, require_metadata: bool) -> Result<Vec<PathBuf>, String> {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read projects directory {}: {}", dir.display(), e))?;
+
+        let mut dirs = Vec::new();
         for entry in entries {
             let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
             let path = entry.path();
+            let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
 
-            if path.is_dir() {
-                let project_info = self.get_project_info(&path)?;
-                projects.push(project_info);
+            if path.is_dir() && !is_hidden && (!require_metadata || path.join(PROJECT_FILE).is_file()) {
+                dirs.push(path);
             }
         }
 
-        projects.sort_by(|a, b| b.modified.cmp(&a.modified));
+        Ok(dirs)
+    }
 
-        Ok(projects)
+    /// Whether `path` is a project this manager may modify: a directory with
+    /// a project.json inside the projects directory or a project root (but
+    /// not one of those itself, nor their trash), or a registered project.
+    fn is_managed_path(&self, path: &Path) -> bool {
+        if path.components().any(|c| c == Component::ParentDir) || !path.join(PROJECT_FILE).is_file() {
+            return false;
+        }
+        self.locations.contains_project(path) || self.containing_root(path).is_some()
+    }
+
+    /// The projects directory or project root that `path` lies strictly
+    /// inside, outside of its trash.
+    fn containing_root(&self, path: &Path) -> Option<PathBuf> {
+        let path = PathBuf::from(canonical_string(path));
+        std::iter::once(canonical_string(&self.projects_dir))
+            .chain(self.locations.roots.iter().cloned())
+            .map(PathBuf::from)
+            .find(|dir| {
+                path.strip_prefix(dir).is_ok_and(|relative| {
+                    relative.components().next().is_some() && !relative.starts_with(TRASH_DIR)
+                })
+            })
+    }
+
+    /// Where a deleted project goes, see `TRASH_DIR`.
+    fn trash_dir_for(&self, project_path: &Path) -> PathBuf {
+        self.containing_root(project_path)
+            .unwrap_or_else(|| self.projects_dir.clone())
+            .join(TRASH_DIR)
+    }
+
+    /// The trash of the projects directory and of every project root.
+    fn trash_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = vec![self.projects_dir.join(TRASH_DIR)];
+        for root in &self.locations.roots {
+            let dir = Path::new(root).join(TRASH_DIR);
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+        dirs
     }
 
-    pub fn create_project(&self, name: &str) -> Result<String, String> {
+    /// Creates a new project, optionally seeded from a template directory.
+    ///
+    /// Files provided by the template take precedence over the built-in
+    /// defaults; anything the template leaves out is filled in as usual.
+    pub fn create_project(&self, name: &str, template_dir: Option<&Path>) -> Result<String, String> {
         let sanitized_name = sanitize_filename::sanitize(name);
         if sanitized_name.is_empty() {
             return Err("Invalid project name".to_string());
@@ -139,6 +386,17 @@ impl ProjectManager {
                 format!("Failed to create project directory: {} (path: {:?})", e, project_path)
             })?;
 
+        if let Some(template_dir) = template_dir {
+            let vars = TemplateVars {
+                project_name: name,
+                project_id: &sanitized_name,
+            };
+            if let Err(e) = instantiate_template(template_dir, &project_path, &vars) {
+                let _ = fs::remove_dir_all(&project_path);
+                return Err(format!("Failed to apply template: {}", e));
+            }
+        }
+
         fs::create_dir_all(project_path.join("assets"))
             .map_err(|e| {
                 format!("Failed to create assets directory: {}", e)
@@ -172,8 +430,10 @@ impl ProjectManager {
                     "exclude": ["node_modules"]
                     }"#;
                 let tsconfig_path = project_path.join("tsconfig.json");
-                fs::write(&tsconfig_path, tsconfig_content)
-                    .map_err(|e| format!("Failed to write tsconfig.json: {}", e))?;
+                if !tsconfig_path.exists() {
+                    fs::write(&tsconfig_path, tsconfig_content)
+                        .map_err(|e| format!("Failed to write tsconfig.json: {}", e))?;
+                }
 
         let package_json_content = serde_json::json!({
             "name": sanitized_name,
@@ -185,110 +445,38 @@ impl ProjectManager {
             }
         });
         let package_json_path = project_path.join("package.json");
-        fs::write(
-            &package_json_path,
-            serde_json::to_string_pretty(&package_json_content)
-                .map_err(|e| format!("Failed to serialize package.json: {}", e))?
-        )
-        .map_err(|e| format!("Failed to write package.json: {}", e))?;
+        if !package_json_path.exists() {
+            fs::write(
+                &package_json_path,
+                serde_json::to_string_pretty(&package_json_content)
+                    .map_err(|e| format!("Failed to serialize package.json: {}", e))?
+            )
+            .map_err(|e| format!("Failed to write package.json: {}", e))?;
+        }
 
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-		let metadata = serde_json::json!({
-			"name": name,
-			"created": now,
-			"modified": now,
-			"version": "1.0.0",
-			"defaultScene": "Main.json",
-			"scenes": {
-				"Main.json": {
-					"includeInBuild": true
-				}
-			},
-			"settings": {
-                "title": "",
-                "editable": false,
-                "vr": false,
-                "renderer": {
-                    "antialias": true,
-                    "shadows": true,
-                    "shadowType": 1,
-                    "toneMapping": 0,
-                    "toneMappingExposure": 1
-                },
-                "defaults": {
-                    "castShadows": false,
-                    "receiveShadows": false,
-                    "material": null
-                }
-            }
-        });
+        let metadata_path = project_path.join(PROJECT_FILE);
+        let metadata = if metadata_path.exists() {
+            let content = fs::read_to_string(&metadata_path)
+                .map_err(|e| format!("Failed to read template project.json: {}", e))?;
+            let (mut template_metadata, _) = ProjectMetadata::from_json_str(&content)
+                .map_err(|e| format!("Template error: {}", e))?;
+            let now = now_secs();
+            template_metadata.name = name.to_string();
+            template_metadata.created = now;
+            template_metadata.modified = now;
+            template_metadata.engine_version = Some(env!("CARGO_PKG_VERSION").to_string());
+            template_metadata
+        } else {
+            ProjectMetadata::new(name)
+        };
 
-        let metadata_path = project_path.join("project.json");
-        fs::write(
-            &metadata_path,
-            serde_json::to_string_pretty(&metadata)
-                .map_err(|e| format!("Failed to serialize metadata: {}", e))?
-        )
-        .map_err(|e| {
-            eprintln!("[ProjectManager] Error writing metadata: {}", e);
-            format!("Failed to write metadata: {}", e)
-        })?;
+        metadata.save(&project_path)
+            .map_err(|e| {
+                eprintln!("[ProjectManager] Error writing metadata: {}", e);
+                e
+            })?;
 
-		let scene_json = serde_json::json!({
-			"metadata": {},
-			"project": {
-				"shadows": true,
-				"shadowType": 1,
-				"toneMapping": 0,
-				"toneMappingExposure": 1
-			},
-			"camera": {
-				"metadata": {
-					"version": 4.7,
-					"type": "Object",
-					"generator": "Object3D.toJSON"
-				},
-				"object": {
-					"uuid": "",
-					"type": "PerspectiveCamera",
-					"name": "Camera",
-					"layers": 1,
-					"matrix": [1,0,0,0,0,0.8944271909999153,-0.44721359549995787,0,0,0.44721359549995787,0.8944271909999153,0,0,5,10,1],
-					"fov": 50,
-					"zoom": 1,
-					"near": 0.01,
-					"far": 1000,
-					"focus": 10,
-					"aspect": 1,
-					"filmGauge": 35,
-					"filmOffset": 0
-				}
-			},
-			"scene": {
-				"metadata": {
-					"version": 4.5,
-					"type": "Object",
-					"generator": "Object3D.toJSON"
-				},
-				"object": {
-					"uuid": "",
-					"type": "Scene",
-					"name": "Scene",
-					"layers": 1,
-					"matrix": [1,0,0,0,0,1,0,0,0,0,1,0,0,0,0,1],
-					"children": []
-				}
-			},
-			"scripts": {},
-			"history": {
-				"undos": [],
-				"redos": []
-			},
-			"environment": null
-		});
+		let scene_json = empty_scene_json();
 		
 		let scenes_dir = project_path.join("scenes");
 		fs::create_dir_all(&scenes_dir)
@@ -297,16 +485,19 @@ impl ProjectManager {
 				format!("Failed to create scenes directory: {}", e)
 			})?;
 		
-		let scene_path = scenes_dir.join("Main.json");
-		fs::write(
-			&scene_path,
-			serde_json::to_string_pretty(&scene_json)
-				.map_err(|e| format!("Failed to serialize scene.json: {}", e))?
-		)
-		.map_err(|e| {
-			eprintln!("[ProjectManager] Error writing scene.json: {}", e);
-			format!("Failed to write scene.json: {}", e)
-		})?;
+		if !scene_store::scene_exists(&project_path, &metadata.default_scene) {
+			scene_store::write_scene_for(
+				&metadata,
+				&project_path,
+				&metadata.default_scene,
+				&serde_json::to_string_pretty(&scene_json)
+					.map_err(|e| format!("Failed to serialize scene.json: {}", e))?
+			)
+			.map_err(|e| {
+				eprintln!("[ProjectManager] Error writing scene.json: {}", e);
+				e
+			})?;
+		}
 
         if !project_path.exists() {
             return Err(format!("Project directory was not created: {:?}", project_path));
@@ -321,9 +512,102 @@ impl ProjectManager {
             return Err(format!("Metadata file was not created: {:?}", metadata_path));
         }
 
+        write_gitignore(&project_path)?;
+
         Ok(project_path.to_string_lossy().to_string())
     }
 
+    /// Packs a project into a single archive at `destination`.
+    pub fn export_project(&self, path: &str, destination: &str) -> Result<(), String> {
+        let project_path = Path::new(path);
+        if !project_path.is_dir() {
+            return Err("Project does not exist".to_string());
+        }
+
+        write_project_archive(project_path, Path::new(destination))
+    }
+
+    /// Unpacks a project archive into the projects directory. If a project
+    /// with the same name already exists the imported one gets a numbered
+    /// name, e.g. "My Game (2)".
+    pub fn import_project(&self, archive_path: &str) -> Result<String, String> {
+        let archive_path = Path::new(archive_path);
+        let mut metadata = read_archive_metadata(archive_path)?;
+
+        let name = Some(metadata.name.clone())
+            .filter(|n| !n.trim().is_empty())
+            .or_else(|| {
+                archive_path.file_stem()
+                    .and_then(|n| n.to_str())
+                    .map(|n| n.to_string())
+            })
+            .ok_or("Invalid project archive: project name is missing")?;
+
+        fs::create_dir_all(&self.projects_dir)
+            .map_err(|e| format!("Failed to create projects directory: {}", e))?;
+
+        let (project_name, project_path) = self.unique_project_path(&name)?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap();
+        let staging_path = self.projects_dir.join(format!(".import-{}", now.as_millis()));
+
+        if let Err(e) = extract_project_archive(archive_path, &staging_path) {
+            let _ = fs::remove_dir_all(&staging_path);
+            return Err(e);
+        }
+
+        metadata.name = project_name;
+        metadata.touch();
+        if let Err(e) = metadata.save(&staging_path) {
+            let _ = fs::remove_dir_all(&staging_path);
+            return Err(e);
+        }
+
+        for dir in ["assets", "build", "scenes"] {
+            let _ = fs::create_dir_all(staging_path.join(dir));
+        }
+
+        if let Err(e) = write_gitignore(&staging_path) {
+            let _ = fs::remove_dir_all(&staging_path);
+            return Err(e);
+        }
+
+        fs::rename(&staging_path, &project_path)
+            .map_err(|e| {
+                let _ = fs::remove_dir_all(&staging_path);
+                format!("Failed to import project: {}", e)
+            })?;
+
+        Ok(project_path.to_string_lossy().to_string())
+    }
+
+    /// Picks a display name and directory that don't clash with an existing
+    /// project, appending " (2)", " (3)", ... to `name` as needed.
+    fn unique_project_path(&self, name: &str) -> Result<(String, PathBuf), String> {
+        let sanitized_name = sanitize_filename::sanitize(name);
+        if sanitized_name.is_empty() {
+            return Err("Invalid project name".to_string());
+        }
+
+        let project_path = self.projects_dir.join(&sanitized_name);
+        if !project_path.exists() {
+            return Ok((name.to_string(), project_path));
+        }
+
+        (2..u32::MAX)
+            .map(|n| {
+                let candidate = format!("{} ({})", name, n);
+                let candidate_path = self.projects_dir.join(sanitize_filename::sanitize(&candidate));
+                (candidate, candidate_path)
+            })
+            .find(|(_, candidate_path)| !candidate_path.exists())
+            .ok_or_else(|| "Failed to find a free project name".to_string())
+    }
+
+    /// Moves a project to the trash. Use `restore_project` to bring it back
+    /// and `purge_trash` to delete it for good.
     pub fn delete_project(&self, path: &str) -> Result<(), String> {
         let project_path = Path::new(path);
         
@@ -331,77 +615,373 @@ impl ProjectManager {
             return Err("Project does not exist".to_string());
         }
 
-        if !project_path.starts_with(&self.projects_dir) {
+        if !self.is_managed_path(project_path) {
             return Err("Invalid project path".to_string());
         }
 
-        fs::remove_dir_all(project_path)
-            .map_err(|e| {
-                format!("Failed to delete project: {}", e)
-            })?;
+        ensure_unlocked(project_path)?;
+
+        let dir_name = project_path.file_name()
+            .and_then(|n| n.to_str())
+            .ok_or("Invalid project path")?;
+        let name = self.metadata.load(project_path)
+            .map(|m| m.name)
+            .unwrap_or_else(|_| dir_name.to_string());
+
+        let trash_dir = self.trash_dir_for(project_path);
+        fs::create_dir_all(&trash_dir)
+            .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap();
+        let trashed = TrashedProject {
+            id: format!("{}-{}", now.as_millis(), dir_name),
+            name,
+            original_path: project_path.to_string_lossy().to_string(),
+            deleted_at: now.as_secs(),
+            registered: self.locations.contains_project(project_path),
+        };
+
+        let manifest_path = trash_dir.join(format!("{}.json", trashed.id));
+        fs::write(
+            &manifest_path,
+            serde_json::to_string_pretty(&trashed)
+                .map_err(|e| format!("Failed to serialize trash entry: {}", e))?
+        )
+        .map_err(|e| format!("Failed to write trash entry: {}", e))?;
+
+        if let Err(e) = move_dir(project_path, &trash_dir.join(&trashed.id)) {
+            let _ = fs::remove_file(&manifest_path);
+            return Err(format!("Failed to delete project: {}", e));
+        }
         
         if project_path.exists() {
             return Err("Failed to delete project: directory still exists".to_string());
         }
 
+        if trashed.registered {
+            let mut locations = self.locations.clone();
+            locations.remove(path);
+            locations.save()?;
+        }
+
         Ok(())
     }
 
-    fn get_project_info(&self, path: &Path) -> Result<ProjectInfo, String> {
-        let name = path.file_name()
-            .and_then(|n| n.to_str())
-            .ok_or("Invalid project path")?
-            .to_string();
+    pub fn list_trashed_projects(&self) -> Result<Vec<TrashedProject>, String> {
+        Ok(self.trashed_entries()?
+            .into_iter()
+            .map(|(_, trashed)| trashed)
+            .collect())
+    }
+
+    /// Trashed projects across all trash directories, most recently deleted
+    /// first, each with the trash directory it is in.
+    fn trashed_entries(&self) -> Result<Vec<(PathBuf, TrashedProject)>, String> {
+        let mut trashed = Vec::new();
 
-        let metadata_path = path.join("project.json");
-        let display_name = if metadata_path.exists() {
-            if let Ok(content) = fs::read_to_string(&metadata_path) {
-                if let Ok(metadata) = serde_json::from_str::<serde_json::Value>(&content) {
-                    metadata.get("name")
-                        .and_then(|n| n.as_str())
-                        .unwrap_or(&name)
-                        .to_string()
-                } else {
-                    name.clone()
+        for trash_dir in self.trash_dirs() {
+            if !trash_dir.exists() {
+                continue;
+            }
+
+            let entries = fs::read_dir(&trash_dir)
+                .map_err(|e| format!("Failed to read trash directory: {}", e))?;
+
+            for entry in entries {
+                let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+                let path = entry.path();
+
+                if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                    continue;
+                }
+
+                let content = fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read trash entry: {}", e))?;
+                match serde_json::from_str::<TrashedProject>(&content) {
+                    Ok(project) if trash_dir.join(&project.id).is_dir() => trashed.push((trash_dir.clone(), project)),
+                    Ok(_) => {}
+                    Err(e) => log::warn!("[ProjectManager] Skipping invalid trash entry {:?}: {}", path, e),
                 }
-            } else {
-                name.clone()
             }
+        }
+
+        trashed.sort_by(|(_, a), (_, b)| b.deleted_at.cmp(&a.deleted_at));
+
+        Ok(trashed)
+    }
+
+    /// Moves a trashed project back to where it was deleted from. If that
+    /// location has been taken in the meantime, the project is restored under
+    /// a numbered name instead.
+    pub fn restore_project(&self, id: &str) -> Result<String, String> {
+        let (trash_dir, trashed) = self.find_trashed_project(id)?;
+        let trashed_path = trash_dir.join(&trashed.id);
+
+        let original_path = PathBuf::from(&trashed.original_path);
+        let restorable = original_path.starts_with(&self.projects_dir)
+            || self.locations.is_in_root(&original_path)
+            || trashed.registered;
+        let (restore_path, renamed_to) = if !original_path.exists() && restorable {
+            (original_path, None)
         } else {
-            name.clone()
+            let (name, path) = self.unique_project_path(&trashed.name)?;
+            (path, Some(name))
         };
 
-        let modified = if metadata_path.exists() {
-            if let Ok(content) = fs::read_to_string(&metadata_path) {
-                if let Ok(metadata) = serde_json::from_str::<serde_json::Value>(&content) {
-                    metadata.get("modified")
-                        .or_else(|| metadata.get("created"))
-                        .and_then(|m| m.as_u64())
-                        .unwrap_or_else(|| {
-                            path.metadata()
-                                .ok()
-                                .and_then(|m| m.modified().ok())
-                                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-                                .map(|d| d.as_secs())
-                                .unwrap_or(0)
-                        })
-                } else {
-                    path.metadata()
-                        .ok()
-                        .and_then(|m| m.modified().ok())
-                        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-                        .map(|d| d.as_secs())
-                        .unwrap_or(0)
+        move_dir(&trashed_path, &restore_path)
+            .map_err(|e| format!("Failed to restore project: {}", e))?;
+        let _ = fs::remove_file(trash_dir.join(format!("{}.json", trashed.id)));
+
+        if let Some(name) = renamed_to {
+            if self.metadata.load(&restore_path).is_ok() {
+                self.metadata.update(&restore_path, |metadata| {
+                    metadata.name = name;
+                    Ok(())
+                })?;
+            }
+        } else if trashed.registered {
+            let mut locations = self.locations.clone();
+            locations.add(&restore_path.to_string_lossy(), LocationKind::Project)?;
+            locations.save()?;
+        }
+
+        Ok(restore_path.to_string_lossy().to_string())
+    }
+
+    /// Permanently deletes trashed projects. With `max_age_days` only entries
+    /// deleted more than that many days ago are removed; otherwise the trash
+    /// is emptied. Returns the number of projects purged.
+    pub fn purge_trash(&self, max_age_days: Option<u64>) -> Result<usize, String> {
+        let cutoff = max_age_days.map(|days| now_secs().saturating_sub(days * 24 * 60 * 60));
+        let mut purged = 0;
+
+        for (trash_dir, trashed) in self.trashed_entries()? {
+            if cutoff.is_some_and(|cutoff| trashed.deleted_at > cutoff) {
+                continue;
+            }
+
+            fs::remove_dir_all(trash_dir.join(&trashed.id))
+                .map_err(|e| format!("Failed to purge project '{}': {}", trashed.name, e))?;
+            let _ = fs::remove_file(trash_dir.join(format!("{}.json", trashed.id)));
+            purged += 1;
+        }
+
+        Ok(purged)
+    }
+
+    fn find_trashed_project(&self, id: &str) -> Result<(PathBuf, TrashedProject), String> {
+        self.trashed_entries()?
+            .into_iter()
+            .find(|(_, p)| p.id == id)
+            .ok_or_else(|| "Project not found in trash".to_string())
+    }
+
+    /// Deep-copies a project under a new display name. `build/` and
+    /// `node_modules/` are not copied, and the copy gets fresh timestamps.
+    pub fn duplicate_project(&self, path: &str, new_name: Option<&str>) -> Result<String, String> {
+        let source_path = Path::new(path);
+        if !source_path.is_dir() {
+            return Err("Project does not exist".to_string());
+        }
+
+        if !self.is_managed_path(source_path) {
+            return Err("Invalid project path".to_string());
+        }
+
+        let mut metadata = self.metadata.load(source_path)?;
+
+        let (project_name, project_path) = match new_name {
+            Some(name) => {
+                let sanitized_name = sanitize_filename::sanitize(name);
+                if sanitized_name.is_empty() {
+                    return Err("Invalid project name".to_string());
                 }
-            } else {
-                path.metadata()
-                    .ok()
-                    .and_then(|m| m.modified().ok())
-                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-                    .map(|d| d.as_secs())
-                    .unwrap_or(0)
+                let project_path = self.projects_dir.join(&sanitized_name);
+                if project_path.exists() {
+                    return Err(format!("Project '{}' already exists", sanitized_name));
+                }
+                (name.to_string(), project_path)
             }
-        } else {
+            None => self.unique_project_path(&format!("{} Copy", metadata.name))?,
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap();
+        let staging_path = self.projects_dir.join(format!(".duplicate-{}", now.as_millis()));
+
+        let result = copy_dir_filtered(source_path, &staging_path, is_non_portable)
+            .and_then(|_| {
+                fs::create_dir_all(staging_path.join("build"))
+                    .map_err(|e| format!("Failed to create build directory: {}", e))
+            })
+            .and_then(|_| {
+                metadata.name = project_name;
+                metadata.created = now.as_secs();
+                metadata.modified = now.as_secs();
+                metadata.save(&staging_path)
+            })
+            .and_then(|_| {
+                fs::rename(&staging_path, &project_path)
+                    .map_err(|e| format!("Failed to duplicate project: {}", e))
+            });
+
+        if let Err(e) = result {
+            let _ = fs::remove_dir_all(&staging_path);
+            return Err(e);
+        }
+
+        Ok(project_path.to_string_lossy().to_string())
+    }
+
+    /// Renames a project: the directory is renamed to the sanitized new name
+    /// and `name` in project.json is updated. If either step fails the
+    /// project is left as it was.
+    pub fn rename_project(&self, path: &str, new_name: &str) -> Result<String, String> {
+        let project_path = Path::new(path);
+        if !project_path.is_dir() {
+            return Err("Project does not exist".to_string());
+        }
+
+        if !self.is_managed_path(project_path) {
+            return Err("Invalid project path".to_string());
+        }
+
+        ensure_unlocked(project_path)?;
+
+        let sanitized_name = sanitize_filename::sanitize(new_name);
+        if sanitized_name.is_empty() {
+            return Err("Invalid project name".to_string());
+        }
+
+        let new_path = project_path.parent()
+            .ok_or("Invalid project path")?
+            .join(&sanitized_name);
+        let same_dir = new_path == project_path;
+        if !same_dir && new_path.exists() {
+            return Err(format!("Project '{}' already exists", sanitized_name));
+        }
+
+        let registered_path = self.locations.contains_project(project_path)
+            .then(|| PathBuf::from(canonical_string(project_path)));
+
+        // Under the project's metadata lock, so no other update of
+        // project.json can slip in between reading and swapping it.
+        self.metadata.with_lock(project_path, |mut metadata| {
+            metadata.name = new_name.to_string();
+            metadata.touch();
+
+            // Stage the new project.json next to the old one, move the
+            // directory, then swap the staged file in with a rename.
+            let staged_name = "project.json.rename";
+            write_atomic(&project_path.join(staged_name), metadata.to_json_string()?)
+                .map_err(|e| format!("Failed to write metadata: {}", e))?;
+
+            if !same_dir {
+                if let Err(e) = fs::rename(project_path, &new_path) {
+                    let _ = fs::remove_file(project_path.join(staged_name));
+                    return Err(format!("Failed to rename project directory: {}", e));
+                }
+            }
+
+            if let Err(e) = fs::rename(new_path.join(staged_name), new_path.join(PROJECT_FILE)) {
+                let _ = fs::remove_file(new_path.join(staged_name));
+                if !same_dir {
+                    let _ = fs::rename(&new_path, project_path);
+                }
+                return Err(format!("Failed to write metadata: {}", e));
+            }
+            Ok(())
+        })?;
+        // `with_lock` dropped what was cached for the old path.
+        self.metadata.invalidate(&new_path);
+
+        if let Some(registered_path) = registered_path.filter(|_| !same_dir) {
+            let mut locations = self.locations.clone();
+            locations.replace_project(&registered_path, &new_path);
+            locations.save()?;
+        }
+
+        Ok(new_path.to_string_lossy().to_string())
+    }
+
+    /// Copies `image_path` into the project as its thumbnail, replacing any
+    /// previous one. `None` removes the thumbnail.
+    pub fn set_project_thumbnail(&self, path: &str, image_path: Option<&str>) -> Result<Option<String>, String> {
+        let project_path = Path::new(path);
+        if !self.is_managed_path(project_path) {
+            return Err("Invalid project path".to_string());
+        }
+
+        self.metadata.update(project_path, |metadata| {
+            let previous = metadata.thumbnail.take().map(|t| project_path.join(t));
+
+            let thumbnail = match image_path {
+                Some(image_path) => {
+                    let image_path = Path::new(image_path);
+                    let extension = image_path.extension()
+                        .and_then(|e| e.to_str())
+                        .map(|e| e.to_ascii_lowercase())
+                        .filter(|e| THUMBNAIL_EXTENSIONS.contains(&e.as_str()))
+                        .ok_or_else(|| format!("Unsupported thumbnail format, expected one of: {}", THUMBNAIL_EXTENSIONS.join(", ")))?;
+
+                    let file_name = format!("thumbnail.{}", extension);
+                    let target = project_path.join(&file_name);
+                    if canonical_string(image_path) != canonical_string(&target) {
+                        fs::copy(image_path, &target)
+                            .map_err(|e| format!("Failed to copy thumbnail: {}", e))?;
+                    }
+                    if let Some(previous) = previous.filter(|p| *p != target) {
+                        let _ = fs::remove_file(previous);
+                    }
+
+                    metadata.thumbnail = Some(file_name);
+                    Some(target.to_string_lossy().to_string())
+                }
+                None => {
+                    if let Some(previous) = previous {
+                        let _ = fs::remove_file(previous);
+                    }
+                    None
+                }
+            };
+
+            Ok(thumbnail)
+        })
+    }
+
+    /// Replaces the project's tags. Tags are trimmed, and empty and
+    /// duplicate (case-insensitive) tags are dropped.
+    pub fn set_project_tags(&self, path: &str, tags: &[String]) -> Result<Vec<String>, String> {
+        let project_path = Path::new(path);
+        if !self.is_managed_path(project_path) {
+            return Err("Invalid project path".to_string());
+        }
+
+        let mut normalized: Vec<String> = Vec::new();
+        for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            if !normalized.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                normalized.push(tag.to_string());
+            }
+        }
+
+        self.metadata.update(project_path, |metadata| {
+            metadata.tags = normalized.clone();
+            Ok(())
+        })?;
+
+        Ok(normalized)
+    }
+
+    fn get_project_info(&self, path: &Path) -> Result<ProjectInfo, String> {
+        let name = path.file_name()
+            .and_then(|n| n.to_str())
+            .ok_or("Invalid project path")?
+            .to_string();
+
+        let fs_modified = || {
             path.metadata()
                 .ok()
                 .and_then(|m| m.modified().ok())
@@ -410,11 +990,537 @@ impl ProjectManager {
                 .unwrap_or(0)
         };
 
+        let (metadata, error) = match self.metadata.load_optional(path) {
+            Ok(metadata) => (metadata, None),
+            Err(e) => (None, Some(e)),
+        };
+
+        let modified = metadata.as_ref()
+            .and_then(|m| [m.modified, m.created].into_iter().find(|t| *t > 0))
+            .unwrap_or_else(fs_modified);
+        let thumbnail = metadata.as_ref()
+            .and_then(|m| m.thumbnail.as_ref())
+            .map(|t| path.join(t))
+            .filter(|t| t.is_file())
+            .map(|t| t.to_string_lossy().to_string());
+
         Ok(ProjectInfo {
-            name: display_name,
+            name: metadata.as_ref().map(|m| m.name.clone()).unwrap_or(name),
             path: path.to_string_lossy().to_string(),
             modified,
+            created: metadata.as_ref().map(|m| m.created).unwrap_or(0),
+            thumbnail,
+            scene_count: scene_store::list_scene_names(path).len(),
+            asset_count: count_files(&path.join("assets"), |p| {
+                p.file_name().and_then(|n| n.to_str()) != Some("assets.json")
+            }),
+            disk_size: dir_size(path, is_non_portable),
+            format_version: metadata.as_ref().map(|m| m.format_version),
+            engine_version: metadata.as_ref().and_then(|m| m.engine_version.clone()),
+            tags: metadata.map(|m| m.tags).unwrap_or_default(),
+            error,
+            missing: false,
         })
     }
 }
 
+/// Directory that holds the bundled `projects/` and `templates/` folders.
+pub(crate) fn get_base_directory() -> Result<PathBuf, String> {
+    let exe_path = std::env::current_exe()
+        .map_err(|e| format!("Failed to get current executable path: {}", e))?;
+    
+    let base_dir = if cfg!(debug_assertions) {
+        // In debug mode, we're running from src/rust, so go up to project root
+        let current_dir = std::env::current_dir()
+            .map_err(|e| format!("Failed to get current directory: {}", e))?;
+        if current_dir.ends_with("rust") {
+            current_dir.parent()
+                .and_then(|p| p.parent())
+                .ok_or("Failed to get project root directory")?
+                .to_path_buf()
+        } else {
+            current_dir
+        }
+    } else {
+        exe_path.parent()
+            .ok_or("Failed to get executable parent directory")?
+            .to_path_buf()
+    };
+    
+    Ok(base_dir)
+}
+
+/// Contents of a new, empty scene file.
+pub(crate) fn empty_scene_json() -> serde_json::Value {
+    serde_json::json!({
+        "metadata": {},
+        "project": {
+            "shadows": true,
+            "shadowType": 1,
+            "toneMapping": 0,
+            "toneMappingExposure": 1
+        },
+        "camera": {
+            "metadata": {
+                "version": 4.7,
+                "type": "Object",
+                "generator": "Object3D.toJSON"
+            },
+            "object": {
+                "uuid": "",
+                "type": "PerspectiveCamera",
+                "name": "Camera",
+                "layers": 1,
+                "matrix": [1,0,0,0,0,0.8944271909999153,-0.44721359549995787,0,0,0.44721359549995787,0.8944271909999153,0,0,5,10,1],
+                "fov": 50,
+                "zoom": 1,
+                "near": 0.01,
+                "far": 1000,
+                "focus": 10,
+                "aspect": 1,
+                "filmGauge": 35,
+                "filmOffset": 0
+            }
+        },
+        "scene": {
+            "metadata": {
+                "version": 4.5,
+                "type": "Object",
+                "generator": "Object3D.toJSON"
+            },
+            "object": {
+                "uuid": "",
+                "type": "Scene",
+                "name": "Scene",
+                "layers": 1,
+                "matrix": [1,0,0,0,0,1,0,0,0,0,1,0,0,0,0,1],
+                "children": []
+            }
+        },
+        "scripts": {},
+        "environment": null
+    })
+}
+
+/// Lines of a project's `.gitignore` that keep the non-portable entries,
+/// which are local to a machine, out of version control.
+fn gitignore_entries() -> Vec<String> {
+    NON_PORTABLE_ENTRIES.iter()
+        .map(|entry| format!("/{}", entry))
+        .collect()
+}
+
+/// The entries from `gitignore_entries` that the project's `.gitignore`
+/// lacks. A missing `.gitignore` lacks all of them.
+pub(crate) fn missing_gitignore_entries(project_path: &Path) -> Result<Vec<String>, String> {
+    let content = match fs::read_to_string(project_path.join(GITIGNORE_FILE)) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read .gitignore: {}", e)),
+    };
+
+    let normalize = |line: &str| line.trim().trim_matches('/').to_string();
+    let present: HashSet<String> = content.lines().map(normalize).collect();
+    Ok(gitignore_entries()
+        .into_iter()
+        .filter(|entry| !present.contains(&normalize(entry)))
+        .collect())
+}
+
+/// Adds the missing `gitignore_entries` to the project's `.gitignore`,
+/// creating it if needed. Existing lines are kept.
+pub(crate) fn write_gitignore(project_path: &Path) -> Result<(), String> {
+    let missing = missing_gitignore_entries(project_path)?;
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let path = project_path.join(GITIGNORE_FILE);
+    let mut content = fs::read_to_string(&path).unwrap_or_default();
+    if !content.is_empty() {
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push('\n');
+    }
+    content.push_str("# Local editor files\n");
+    for entry in missing {
+        content.push_str(&entry);
+        content.push('\n');
+    }
+
+    write_atomic(&path, content)
+        .map_err(|e| format!("Failed to write .gitignore: {}", e))
+}
+
+/// Number of files below `dir` for which `filter` returns true. A missing
+/// directory counts as empty.
+fn count_files(dir: &Path, filter: impl Fn(&Path) -> bool + Copy) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                count_files(&path, filter)
+            } else {
+                filter(&path) as usize
+            }
+        })
+        .sum()
+}
+
+/// Total size in bytes of the files below `dir`, leaving out the entries for
+/// which `skip` returns true given their path relative to `dir`. Unreadable
+/// entries are skipped.
+fn dir_size(dir: &Path, skip: impl Fn(&Path) -> bool + Copy) -> u64 {
+    fn size_below(root: &Path, dir: &Path, skip: impl Fn(&Path) -> bool + Copy) -> u64 {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return 0;
+        };
+
+        entries
+            .flatten()
+            .filter(|entry| !entry.path().strip_prefix(root).is_ok_and(skip))
+            .map(|entry| match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => size_below(root, &entry.path(), skip),
+                Ok(metadata) => metadata.len(),
+                Err(_) => 0,
+            })
+            .sum()
+    }
+
+    size_below(dir, dir, skip)
+}
+
+/// Recursively copies `src` into `dst`, skipping the entries for which
+/// `skip` returns true given their path relative to `src`.
+fn copy_dir_filtered(src: &Path, dst: &Path, skip: impl Fn(&Path) -> bool + Copy) -> Result<(), String> {
+    copy_dir_below(src, src, dst, skip)
+}
+
+fn copy_dir_below(root: &Path, src: &Path, dst: &Path, skip: impl Fn(&Path) -> bool + Copy) -> Result<(), String> {
+    fs::create_dir_all(dst)
+        .map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    let entries = fs::read_dir(src)
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+
+        if path.strip_prefix(root).is_ok_and(skip) {
+            continue;
+        }
+
+        if path.is_dir() {
+            copy_dir_below(root, &path, &dst_path, skip)?;
+        } else {
+            fs::copy(&path, &dst_path)
+                .map_err(|e| format!("Failed to copy {}: {}", path.display(), e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Moves a directory, falling back to copy-and-delete when a plain rename is
+/// not possible (e.g. across file systems).
+fn move_dir(src: &Path, dst: &Path) -> Result<(), String> {
+    if dst.starts_with(src) {
+        return Err(format!("Cannot move {} into itself", src.display()));
+    }
+
+    if fs::rename(src, dst).is_ok() {
+        return Ok(());
+    }
+
+    copy_and_remove_dir(src, dst)
+}
+
+/// The fallback of `move_dir`: copies `src` to `dst`, then removes `src`.
+/// A failed copy is undone.
+fn copy_and_remove_dir(src: &Path, dst: &Path) -> Result<(), String> {
+    if let Err(e) = copy_dir_filtered(src, dst, |_| false) {
+        let _ = fs::remove_dir_all(dst);
+        return Err(e);
+    }
+
+    fs::remove_dir_all(src)
+        .map_err(|e| format!("Failed to remove {}: {}", src.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("project-manager-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path.canonicalize().unwrap()
+    }
+
+    fn manager(projects_dir: &Path, roots: &[&Path]) -> ProjectManager {
+        let mut locations = ProjectLocations::default();
+        locations.roots = roots.iter().map(|root| canonical_string(root)).collect();
+        ProjectManager {
+            projects_dir: projects_dir.to_path_buf(),
+            locations,
+            metadata: Arc::default(),
+        }
+    }
+
+    fn project(dir: &Path, name: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::create_dir_all(path.join("scenes")).unwrap();
+        ProjectMetadata::new(name).save(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn refuses_to_delete_anything_but_a_project() {
+        let base = temp_dir("delete-guard");
+        let projects_dir = base.join("projects");
+        let root = base.join("root");
+        project(&projects_dir, "Game");
+        fs::create_dir_all(&root).unwrap();
+        // Even with a project.json of their own, the directories holding
+        // projects are not projects this manager may delete.
+        ProjectMetadata::new("Projects").save(&projects_dir).unwrap();
+        ProjectMetadata::new("Root").save(&root).unwrap();
+        let manager = manager(&projects_dir, &[&root]);
+
+        let plain_dir = projects_dir.join("Plain");
+        fs::create_dir_all(&plain_dir).unwrap();
+        let trashed = project(&projects_dir.join(TRASH_DIR), "Old");
+        let escaping = projects_dir.join("Game").join("..").join("Game");
+
+        for path in [&projects_dir, &root, &plain_dir, &trashed, &escaping] {
+            assert_eq!(
+                manager.delete_project(&path.to_string_lossy()),
+                Err("Invalid project path".to_string()),
+                "{}", path.display()
+            );
+        }
+        assert!(projects_dir.join("Game").join(PROJECT_FILE).is_file());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn trashes_projects_next_to_their_root() {
+        let base = temp_dir("trash");
+        let projects_dir = base.join("projects");
+        let root = base.join("root");
+        fs::create_dir_all(&projects_dir).unwrap();
+        let path = project(&root, "Level");
+        let manager = manager(&projects_dir, &[&root]);
+
+        manager.delete_project(&path.to_string_lossy()).unwrap();
+        assert!(!path.exists());
+        assert!(!projects_dir.join(TRASH_DIR).exists());
+
+        let trashed = manager.list_trashed_projects().unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].name, "Level");
+        assert!(root.join(TRASH_DIR).join(&trashed[0].id).join(PROJECT_FILE).is_file());
+
+        let restored = manager.restore_project(&trashed[0].id).unwrap();
+        assert_eq!(Path::new(&restored), path);
+        assert!(path.join(PROJECT_FILE).is_file());
+        assert!(manager.list_trashed_projects().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn purges_every_trash() {
+        let base = temp_dir("purge");
+        let projects_dir = base.join("projects");
+        let root = base.join("root");
+        let manager = manager(&projects_dir, &[&root]);
+        for path in [project(&projects_dir, "A"), project(&root, "B")] {
+            manager.delete_project(&path.to_string_lossy()).unwrap();
+        }
+
+        assert_eq!(manager.list_trashed_projects().unwrap().len(), 2);
+        assert_eq!(manager.purge_trash(Some(1)).unwrap(), 0);
+        assert_eq!(manager.purge_trash(None).unwrap(), 2);
+        assert!(manager.list_trashed_projects().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn moves_directories_by_copying_when_renaming_fails() {
+        let base = temp_dir("move");
+        let src = project(&base, "Source");
+        fs::write(src.join("scenes").join("Main.json"), "{}").unwrap();
+
+        // The copy fallback `move_dir` uses across file systems.
+        let dst = base.join("Moved");
+        copy_and_remove_dir(&src, &dst).unwrap();
+        assert!(!src.exists());
+        assert_eq!(fs::read_to_string(dst.join("scenes").join("Main.json")).unwrap(), "{}");
+        assert!(dst.join(PROJECT_FILE).is_file());
+
+        assert!(move_dir(&dst, &dst.join(TRASH_DIR).join("x")).is_err());
+        assert!(dst.join(PROJECT_FILE).is_file());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    fn name_on_disk(project_path: &Path) -> String {
+        ProjectMetadataStore::new().load(project_path).unwrap().name
+    }
+
+    /// Lists the entries of a directory, to check that nothing was left
+    /// behind.
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn duplicates_projects_without_non_portable_entries() {
+        let base = temp_dir("duplicate");
+        let source = project(&base, "Game");
+        fs::write(source.join("scenes").join("Main.json"), "{}").unwrap();
+        fs::create_dir_all(source.join(scene_store::BACKUP_PATH)).unwrap();
+        fs::write(source.join(scene_store::BACKUP_PATH).join("Main.json.1"), "{}").unwrap();
+        fs::create_dir_all(source.join("node_modules").join("three")).unwrap();
+        fs::write(source.join(LOCK_FILE), "").unwrap();
+        let manager = manager(&base, &[]);
+
+        let copy = PathBuf::from(manager.duplicate_project(&source.to_string_lossy(), None).unwrap());
+        assert_eq!(copy, base.join("Game Copy"));
+        assert_eq!(name_on_disk(&copy), "Game Copy");
+        assert_eq!(fs::read_to_string(copy.join("scenes").join("Main.json")).unwrap(), "{}");
+        assert!(copy.join("build").is_dir());
+        assert!(!copy.join(scene_store::BACKUP_PATH).exists());
+        assert!(!copy.join("node_modules").exists());
+        assert!(!copy.join(LOCK_FILE).exists());
+
+        let second = manager.duplicate_project(&source.to_string_lossy(), None).unwrap();
+        assert_eq!(PathBuf::from(second), base.join("Game Copy (2)"));
+        assert_eq!(
+            manager.duplicate_project(&source.to_string_lossy(), Some("Game Copy")),
+            Err("Project 'Game Copy' already exists".to_string())
+        );
+        // No staging directory is left behind.
+        assert_eq!(entries(&base), ["Game", "Game Copy", "Game Copy (2)"]);
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn renames_the_directory_along_with_the_project() {
+        let base = temp_dir("rename");
+        let source = project(&base, "Game");
+        project(&base, "Taken");
+        let manager = manager(&base, &[]);
+
+        let renamed = PathBuf::from(manager.rename_project(&source.to_string_lossy(), "Sequel: Part 2").unwrap());
+        assert_eq!(renamed, base.join(sanitize_filename::sanitize("Sequel: Part 2")));
+        assert!(!source.exists());
+        assert_eq!(name_on_disk(&renamed), "Sequel: Part 2");
+        assert!(!renamed.join("project.json.rename").exists());
+
+        assert_eq!(
+            manager.rename_project(&renamed.to_string_lossy(), "Taken"),
+            Err("Project 'Taken' already exists".to_string())
+        );
+        assert!(renamed.join(PROJECT_FILE).is_file());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn lists_project_stats() {
+        let base = temp_dir("stats");
+        let game = project(&base, "Game");
+        fs::write(game.join("scenes").join("Main.json"), "{}").unwrap();
+        fs::write(game.join("scenes").join("Menu.json"), "{}").unwrap();
+        fs::create_dir_all(game.join("assets").join("textures")).unwrap();
+        fs::write(game.join("assets").join("textures").join("wood.png"), [0u8; 64]).unwrap();
+        fs::write(game.join("assets").join("assets.json"), "{}").unwrap();
+        let manager = manager(&base, &[]);
+
+        let info = manager.get_project_info(&game).unwrap();
+        assert_eq!(info.name, "Game");
+        assert_eq!(info.scene_count, 2);
+        assert_eq!(info.asset_count, 1);
+        assert!(info.disk_size >= 64);
+        assert!(info.created > 0 && info.format_version.is_some());
+
+        // Generated and machine specific entries don't add to the size.
+        fs::create_dir_all(game.join("node_modules")).unwrap();
+        fs::write(game.join("node_modules").join("three.js"), [0u8; 4096]).unwrap();
+        assert_eq!(manager.get_project_info(&game).unwrap().disk_size, info.disk_size);
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn filters_and_sorts_by_tags_and_stats() {
+        let base = temp_dir("query");
+        let small = project(&base, "Small");
+        let large = project(&base, "Large");
+        fs::write(large.join("scenes").join("Main.json"), "{}").unwrap();
+        let manager = manager(&base, &[]);
+
+        let tags = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            manager.set_project_tags(&small.to_string_lossy(), &tags(&[" jam ", "", "Jam", "3d"])).unwrap(),
+            ["jam", "3d"]
+        );
+        manager.set_project_tags(&large.to_string_lossy(), &tags(&["3D"])).unwrap();
+
+        let names = |query: ProjectQuery| -> Vec<String> {
+            manager.list_projects(&query).unwrap().into_iter().map(|p| p.name).collect()
+        };
+        assert_eq!(names(ProjectQuery { tags: tags(&["JAM"]), ..Default::default() }), ["Small"]);
+        assert_eq!(names(ProjectQuery { search: Some("ja".into()), ..Default::default() }), ["Small"]);
+        assert_eq!(names(ProjectQuery { search: Some("arg".into()), ..Default::default() }), ["Large"]);
+        assert_eq!(
+            names(ProjectQuery { tags: tags(&["3d"]), sort_by: ProjectSortKey::SceneCount, ..Default::default() }),
+            ["Large", "Small"]
+        );
+        assert_eq!(
+            names(ProjectQuery { sort_by: ProjectSortKey::Name, ascending: true, ..Default::default() }),
+            ["Large", "Small"]
+        );
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn copies_thumbnails_into_the_project() {
+        let base = temp_dir("thumbnail");
+        let game = project(&base, "Game");
+        let image = base.join("Shot.PNG");
+        fs::write(&image, [0u8; 8]).unwrap();
+        let manager = manager(&base, &[]);
+        let path = game.to_string_lossy();
+
+        assert!(manager.set_project_thumbnail(&path, Some(&base.join("notes.txt").to_string_lossy())).is_err());
+        let thumbnail = manager.set_project_thumbnail(&path, Some(&image.to_string_lossy())).unwrap();
+        assert_eq!(thumbnail, Some(game.join("thumbnail.png").to_string_lossy().to_string()));
+        assert_eq!(manager.get_project_info(&game).unwrap().thumbnail, thumbnail);
+
+        assert_eq!(manager.set_project_thumbnail(&path, None).unwrap(), None);
+        assert!(!game.join("thumbnail.png").exists());
+        assert_eq!(manager.get_project_info(&game).unwrap().thumbnail, None);
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
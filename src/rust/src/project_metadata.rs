@@ -0,0 +1,406 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::atomic_write::write_atomic;
+
+/// Current version of the project.json format. Bump it together with a new
+/// entry in `MIGRATIONS`.
+pub const PROJECT_FORMAT_VERSION: u32 = 1;
+
+pub const PROJECT_FILE: &str = "project.json";
+
+pub const DEFAULT_SCENE: &str = "Main.json";
+
+/// Top-level keys of project.json the backend reads. Anything else ends up
+/// in `ProjectMetadata::extra`.
+const KNOWN_KEYS: &[&str] = &[
+    "formatVersion", "name", "created", "modified", "version", "defaultScene", "scenes",
+    "engineVersion", "thumbnail", "tags", "sceneStorage", "sceneEncoding", "settings",
+];
+
+/// Upgrades a raw project.json object by one format version.
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// `MIGRATIONS[n]` upgrades a document from format version `n` to `n + 1`.
+/// Files written before `formatVersion` existed are version 0.
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1];
+
+/// Typed contents of a project's project.json.
+///
+/// Keys the backend doesn't know about are kept in `extra` so that data
+/// written by the editor frontend round-trips untouched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectMetadata {
+    pub format_version: u32,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub created: u64,
+    #[serde(default)]
+    pub modified: u64,
+    #[serde(default = "default_version")]
+    pub version: String,
+    #[serde(default = "default_scene")]
+    pub default_scene: String,
+    #[serde(default)]
+    pub scenes: BTreeMap<String, SceneConfig>,
+    /// Editor version that created the project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine_version: Option<String>,
+    /// Image shown in the project hub, relative to the project directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// On-disk layout of the project's scenes.
+    #[serde(default, skip_serializing_if = "SceneStorage::is_default")]
+    pub scene_storage: SceneStorage,
+    /// Encoding of single-file scenes.
+    #[serde(default, skip_serializing_if = "SceneEncoding::is_default")]
+    pub scene_encoding: SceneEncoding,
+    /// Runtime and editor settings. Owned by the frontend, so left untyped.
+    #[serde(default = "default_settings")]
+    pub settings: Value,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneConfig {
+    #[serde(default = "default_true")]
+    pub include_in_build: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// How scenes are laid out in `scenes/`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SceneStorage {
+    /// One `<name>.json` file per scene.
+    #[default]
+    File,
+    /// A `<name>.scene/` directory per scene, with a file per top-level
+    /// object and per resource table, so that changes diff and merge well.
+    Split,
+}
+
+impl SceneStorage {
+    fn is_default(&self) -> bool {
+        *self == SceneStorage::File
+    }
+}
+
+/// How single-file scenes are encoded. Split scenes are always JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SceneEncoding {
+    /// Pretty-printed `<name>.json`.
+    #[default]
+    Json,
+    /// `<name>.cbor`, with geometry buffers stored as packed typed arrays.
+    Cbor,
+}
+
+impl SceneEncoding {
+    fn is_default(&self) -> bool {
+        *self == SceneEncoding::Json
+    }
+}
+
+impl Default for SceneConfig {
+    fn default() -> Self {
+        SceneConfig {
+            include_in_build: true,
+            extra: Map::new(),
+        }
+    }
+}
+
+fn default_version() -> String {
+    "1.0.0".to_string()
+}
+
+fn default_scene() -> String {
+    DEFAULT_SCENE.to_string()
+}
+
+fn default_settings() -> Value {
+    Value::Object(Map::new())
+}
+
+fn default_true() -> bool {
+    true
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+impl ProjectMetadata {
+    /// Metadata for a freshly created project with a single Main scene.
+    pub fn new(name: &str) -> Self {
+        let now = now_secs();
+        let mut scenes = BTreeMap::new();
+        scenes.insert(DEFAULT_SCENE.to_string(), SceneConfig::default());
+
+        ProjectMetadata {
+            format_version: PROJECT_FORMAT_VERSION,
+            name: name.to_string(),
+            created: now,
+            modified: now,
+            version: default_version(),
+            default_scene: default_scene(),
+            scenes,
+            engine_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            thumbnail: None,
+            tags: Vec::new(),
+            scene_storage: SceneStorage::File,
+            scene_encoding: SceneEncoding::Json,
+            settings: serde_json::json!({
+                "title": "",
+                "editable": false,
+                "vr": false,
+                "renderer": {
+                    "antialias": true,
+                    "shadows": true,
+                    "shadowType": 1,
+                    "toneMapping": 0,
+                    "toneMappingExposure": 1
+                },
+                "defaults": {
+                    "castShadows": false,
+                    "receiveShadows": false,
+                    "material": null
+                }
+            }),
+            extra: Map::new(),
+        }
+    }
+
+    /// Parses project.json content, migrating it to the current format
+    /// version. The flag is `true` when a migration was applied.
+    pub fn from_json_str(content: &str) -> Result<(Self, bool), String> {
+        let value = serde_json::from_str::<Value>(content)
+            .map_err(|e| format!("Invalid project.json: {}", e))?;
+        Self::from_value(value)
+    }
+
+    pub fn from_value(value: Value) -> Result<(Self, bool), String> {
+        let Value::Object(mut fields) = value else {
+            return Err("Invalid project.json: expected an object".to_string());
+        };
+
+        let version = match fields.get("formatVersion") {
+            None => 0,
+            Some(v) => v.as_u64()
+                .ok_or("Invalid project.json: formatVersion must be a non-negative integer")?,
+        };
+
+        if version > PROJECT_FORMAT_VERSION as u64 {
+            return Err(format!(
+                "project.json uses format version {}, but this editor only supports up to {}. Please update the editor.",
+                version, PROJECT_FORMAT_VERSION
+            ));
+        }
+
+        let migrated = version < PROJECT_FORMAT_VERSION as u64;
+        for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            migration(&mut fields)
+                .map_err(|e| format!("Failed to migrate project.json from format version {}: {}", from, e))?;
+            fields.insert("formatVersion".to_string(), Value::from(from as u64 + 1));
+        }
+
+        let metadata: ProjectMetadata = serde_path_to_error::deserialize(Value::Object(fields))
+            .map_err(|e| format!("Invalid project.json: {}: {}", e.path(), e.inner()))?;
+        metadata.validate()?;
+
+        Ok((metadata, migrated))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !is_valid_scene_name(&self.default_scene) {
+            return Err(format!(
+                "Invalid project.json: defaultScene '{}' is not a valid scene file name",
+                self.default_scene
+            ));
+        }
+
+        if let Some(name) = self.scenes.keys().find(|name| !is_valid_scene_name(name)) {
+            return Err(format!(
+                "Invalid project.json: scenes has an entry '{}' that is not a valid scene file name",
+                name
+            ));
+        }
+
+        for warning in self.warnings() {
+            log::warn!("[Project] project.json: {}", warning);
+        }
+
+        Ok(())
+    }
+
+    /// Things that don't keep the project from loading but are likely not
+    /// what was meant: unknown keys that look like a misspelt known one, and
+    /// a `defaultScene` without an entry in `scenes`.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = self.extra.keys()
+            .filter_map(|key| {
+                let known = KNOWN_KEYS.iter().find(|known| is_near_miss(key, known))?;
+                Some(format!("unknown key '{}' is ignored, did you mean '{}'?", key, known))
+            })
+            .collect();
+
+        if !self.scenes.contains_key(&self.default_scene) {
+            warnings.push(format!("defaultScene '{}' is not listed in scenes", self.default_scene));
+        }
+        warnings
+    }
+
+    /// Parses the content of a project's project.json, migrating it in
+    /// memory. A missing `name` falls back to the directory name. Returns
+    /// whether it had to be migrated, like `from_json_str`. Projects are read
+    /// through `ProjectMetadataStore`, which caches the result.
+    pub fn from_file_content(project_path: &Path, content: &str) -> Result<(Self, bool), String> {
+        let (mut metadata, migrated) = Self::from_json_str(content)?;
+        if metadata.name.is_empty() {
+            if let Some(dir_name) = project_path.file_name().and_then(|n| n.to_str()) {
+                metadata.name = dir_name.to_string();
+            }
+        }
+
+        Ok((metadata, migrated))
+    }
+
+    pub fn to_json_string(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize metadata: {}", e))
+    }
+
+    pub fn save(&self, project_path: &Path) -> Result<(), String> {
+        write_atomic(&project_path.join(PROJECT_FILE), self.to_json_string()?)
+            .map_err(|e| format!("Failed to write metadata: {}", e))
+    }
+
+    /// Sets `modified` to the current time.
+    pub fn touch(&mut self) {
+        self.modified = now_secs();
+    }
+
+    pub fn scene_config(&self, scene_name: &str) -> SceneConfig {
+        self.scenes.get(scene_name).cloned().unwrap_or_default()
+    }
+}
+
+/// Scene entries are plain `.json` file names inside `scenes/`.
+pub fn is_valid_scene_name(name: &str) -> bool {
+    name.ends_with(".json")
+        && name.len() > ".json".len()
+        && !name.contains(['/', '\\'])
+}
+
+/// Whether `key` differs from `known` only in case or by a typo: at most one
+/// edit per four characters of `known`, and no more than two.
+fn is_near_miss(key: &str, known: &str) -> bool {
+    if key.eq_ignore_ascii_case(known) {
+        return true;
+    }
+    let max_distance = (known.len() / 4).min(2);
+    edit_distance(&key.to_ascii_lowercase(), &known.to_ascii_lowercase()) <= max_distance
+}
+
+/// Levenshtein distance between two strings, by characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Version 0 files predate `formatVersion`. They could lack `scenes`
+/// altogether or store `defaultScene` as null; normalize both.
+fn migrate_v0_to_v1(fields: &mut Map<String, Value>) -> Result<(), String> {
+    if matches!(fields.get("defaultScene"), Some(Value::Null)) {
+        fields.remove("defaultScene");
+    }
+
+    let default_scene = fields.get("defaultScene")
+        .and_then(|s| s.as_str())
+        .unwrap_or(DEFAULT_SCENE)
+        .to_string();
+
+    match fields.get_mut("scenes") {
+        None | Some(Value::Null) => {
+            let mut scenes = Map::new();
+            scenes.insert(default_scene, serde_json::json!({ "includeInBuild": true }));
+            fields.insert("scenes".to_string(), Value::Object(scenes));
+        }
+        Some(Value::Object(scenes)) => {
+            for config in scenes.values_mut() {
+                if config.is_null() {
+                    *config = serde_json::json!({ "includeInBuild": true });
+                }
+            }
+        }
+        Some(_) => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> ProjectMetadata {
+        ProjectMetadata::from_json_str(content).unwrap().0
+    }
+
+    #[test]
+    fn warns_about_misspelt_keys() {
+        let metadata = parse(r#"{
+            "formatVersion": 1,
+            "defaultScen": "Level.json",
+            "scene": {},
+            "Tags": [],
+            "editorLayout": {}
+        }"#);
+        assert_eq!(metadata.warnings(), vec![
+            "unknown key 'Tags' is ignored, did you mean 'tags'?".to_string(),
+            "unknown key 'defaultScen' is ignored, did you mean 'defaultScene'?".to_string(),
+            "unknown key 'scene' is ignored, did you mean 'scenes'?".to_string(),
+            "defaultScene 'Main.json' is not listed in scenes".to_string(),
+        ]);
+        // Unknown keys still round-trip.
+        assert!(metadata.extra.contains_key("editorLayout"));
+    }
+
+    #[test]
+    fn warns_about_a_dangling_default_scene() {
+        let metadata = parse(r#"{ "formatVersion": 1, "defaultScene": "Level.json", "scenes": { "Main.json": {} } }"#);
+        assert_eq!(metadata.warnings(), vec!["defaultScene 'Level.json' is not listed in scenes".to_string()]);
+
+        assert!(ProjectMetadata::new("Game").warnings().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_scene_names() {
+        assert!(ProjectMetadata::from_json_str(r#"{ "formatVersion": 1, "defaultScene": "../Main.json" }"#).is_err());
+        assert!(ProjectMetadata::from_json_str(r#"{ "formatVersion": 1, "scenes": { "Main": {} } }"#).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use crate::atomic_write::write_atomic;
+use crate::project_metadata::{ProjectMetadata, PROJECT_FILE};
+use crate::revision::{self, Revisioned};
+
+/// Parsed project.json of every project the backend has touched.
+///
+/// Reads are served from the cache as long as the file on disk is unchanged.
+/// Mutations of a project are serialized: each one runs on the current
+/// metadata and is saved before the next one starts, so concurrent commands
+/// can't lose each other's updates.
+#[derive(Default)]
+pub struct ProjectMetadataStore {
+    projects: Mutex<HashMap<PathBuf, Arc<Mutex<Entry>>>>,
+}
+
+#[derive(Default)]
+struct Entry {
+    cached: Option<Cached>,
+}
+
+struct Cached {
+    metadata: ProjectMetadata,
+    /// The file as it is on disk.
+    content: String,
+    /// Whether `metadata` was migrated from an older format than `content`.
+    migrated: bool,
+    /// Revision of `content`, to notice changes made outside the store.
+    /// Unlike the modification time and size, it also catches a rewrite of
+    /// the same length within the file system's timestamp resolution.
+    revision: String,
+}
+
+impl Entry {
+    /// The project.json on disk, parsed again only if its content changed.
+    fn current(&mut self, project_path: &Path) -> Result<Option<&Cached>, String> {
+        let content = match fs::read_to_string(project_path.join(PROJECT_FILE)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.cached = None;
+                return Ok(None);
+            }
+            Err(e) => return Err(format!("Failed to read project.json: {}", e)),
+        };
+
+        let revision = revision::revision_of(content.as_bytes());
+        let unchanged = matches!(&self.cached, Some(cached) if cached.revision == revision);
+        if !unchanged {
+            self.cached = None;
+            let (metadata, migrated) = ProjectMetadata::from_file_content(project_path, &content)?;
+            self.cached = Some(Cached { metadata, content, migrated, revision });
+        }
+        Ok(self.cached.as_ref())
+    }
+
+    fn metadata(&mut self, project_path: &Path) -> Result<Option<ProjectMetadata>, String> {
+        Ok(self.current(project_path)?.map(|cached| cached.metadata.clone()))
+    }
+
+    fn save(&mut self, project_path: &Path, metadata: ProjectMetadata) -> Result<(), String> {
+        self.cached = None;
+        let content = metadata.to_json_string()?;
+        write_atomic(&project_path.join(PROJECT_FILE), &content)
+            .map_err(|e| format!("Failed to write metadata: {}", e))?;
+        let revision = revision::revision_of(content.as_bytes());
+        self.cached = Some(Cached { metadata, content, migrated: false, revision });
+        Ok(())
+    }
+}
+
+/// The key a project is cached under. The same project may be reached
+/// through different paths, so the longest existing part of the path is
+/// canonicalized and the rest appended as is. That keeps the key the same
+/// before and after the project's directory is created.
+fn cache_key(project_path: &Path) -> PathBuf {
+    let mut absolute = std::env::current_dir().unwrap_or_default();
+    for component in project_path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            component => absolute.push(component),
+        }
+    }
+
+    for ancestor in absolute.ancestors() {
+        if let Ok(canonical) = ancestor.canonicalize() {
+            let rest = absolute.strip_prefix(ancestor).unwrap_or(Path::new(""));
+            return if rest.as_os_str().is_empty() { canonical } else { canonical.join(rest) };
+        }
+    }
+    absolute
+}
+
+impl ProjectMetadataStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entry(&self, project_path: &Path) -> Arc<Mutex<Entry>> {
+        self.projects.lock().entry(cache_key(project_path)).or_default().clone()
+    }
+
+    /// The project's metadata, migrated in memory if it is in an older
+    /// format.
+    pub fn load(&self, project_path: &Path) -> Result<ProjectMetadata, String> {
+        self.load_optional(project_path)?
+            .ok_or_else(|| "project.json not found".to_string())
+    }
+
+    /// Like `load`, but returns `None` when the project has no project.json.
+    pub fn load_optional(&self, project_path: &Path) -> Result<Option<ProjectMetadata>, String> {
+        let entry = self.entry(project_path);
+        let mut entry = entry.lock();
+        entry.metadata(project_path)
+    }
+
+    /// project.json as it is on disk, along with its revision, or `None`
+    /// when the project has none. The content is still checked to be valid
+    /// metadata.
+    pub fn read_content(&self, project_path: &Path) -> Result<Option<Revisioned>, String> {
+        let entry = self.entry(project_path);
+        let mut entry = entry.lock();
+        Ok(entry.current(project_path)?.map(|cached| Revisioned {
+            revision: Some(revision::project_config_revision_of(cached.content.as_bytes())),
+            content: cached.content.clone(),
+        }))
+    }
+
+    /// Like `load`, but writes project.json back if it had to be migrated
+    /// from an older format.
+    pub fn open(&self, project_path: &Path) -> Result<ProjectMetadata, String> {
+        let entry = self.entry(project_path);
+        let mut entry = entry.lock();
+        let Some(cached) = entry.current(project_path)? else {
+            return Err("project.json not found".to_string());
+        };
+
+        let (metadata, migrated) = (cached.metadata.clone(), cached.migrated);
+        if migrated {
+            entry.save(project_path, metadata.clone())?;
+        }
+        Ok(metadata)
+    }
+
+    /// Applies `f` to the project's metadata and saves the result. Nothing
+    /// is saved if `f` fails.
+    pub fn update<R>(&self, project_path: &Path, f: impl FnOnce(&mut ProjectMetadata) -> Result<R, String>) -> Result<R, String> {
+        self.update_optional(project_path, f)?
+            .ok_or_else(|| "project.json not found".to_string())
+    }
+
+    /// Like `update`, but does nothing and returns `None` when the project
+    /// has no project.json.
+    pub fn update_optional<R>(&self, project_path: &Path, f: impl FnOnce(&mut ProjectMetadata) -> Result<R, String>) -> Result<Option<R>, String> {
+        let entry = self.entry(project_path);
+        let mut entry = entry.lock();
+        let Some(mut metadata) = entry.metadata(project_path)? else {
+            return Ok(None);
+        };
+
+        let result = f(&mut metadata)?;
+        entry.save(project_path, metadata)?;
+        Ok(Some(result))
+    }
+
+    /// Sets `modified` to the current time, if the project has a
+    /// project.json.
+    pub fn touch(&self, project_path: &Path) -> Result<(), String> {
+        self.update_optional(project_path, |metadata| {
+            metadata.touch();
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// Writes `metadata` as the project's project.json, whatever is there.
+    pub fn save(&self, project_path: &Path, metadata: ProjectMetadata) -> Result<(), String> {
+        let entry = self.entry(project_path);
+        let mut entry = entry.lock();
+        entry.save(project_path, metadata)
+    }
+
+    /// Like `save`, but fails with a conflict if project.json isn't at
+    /// `expected_revision`. Returns the new revision.
+    pub fn replace(&self, project_path: &Path, metadata: ProjectMetadata, expected_revision: Option<&str>) -> Result<String, String> {
+        let entry = self.entry(project_path);
+        let mut entry = entry.lock();
+
+        let current = revision::project_config_revision(project_path)?;
+        revision::check_revision(PROJECT_FILE, current.as_deref(), expected_revision)?;
+
+        entry.save(project_path, metadata)?;
+        revision::project_config_revision(project_path)?
+            .ok_or_else(|| "Project config not found after saving".to_string())
+    }
+
+    /// Runs `f` on the project's metadata while holding the project's lock,
+    /// for changes that write project.json other than through the store,
+    /// e.g. along with moving the project. The cache is dropped afterwards.
+    pub fn with_lock<R>(&self, project_path: &Path, f: impl FnOnce(ProjectMetadata) -> Result<R, String>) -> Result<R, String> {
+        let entry = self.entry(project_path);
+        let mut entry = entry.lock();
+        let metadata = entry.metadata(project_path)?
+            .ok_or_else(|| "project.json not found".to_string())?;
+
+        let result = f(metadata);
+        entry.cached = None;
+        result
+    }
+
+    /// Drops the cached metadata of a project, e.g. after project.json was
+    /// changed outside the editor or the project was moved. The entry
+    /// itself stays, as a command may be holding its lock.
+    pub fn invalidate(&self, project_path: &Path) {
+        self.entry(project_path).lock().cached = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("project-metadata-store-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn notices_rewrites_of_the_same_size() {
+        let project = temp_dir("rewrite");
+        let store = ProjectMetadataStore::new();
+        store.save(&project, ProjectMetadata::new("Alpha")).unwrap();
+        assert_eq!(store.load(&project).unwrap().name, "Alpha");
+
+        // Same length, and likely within the same timestamp tick.
+        let content = fs::read_to_string(project.join(PROJECT_FILE)).unwrap();
+        fs::write(project.join(PROJECT_FILE), content.replace("Alpha", "Omega")).unwrap();
+        assert_eq!(store.load(&project).unwrap().name, "Omega");
+
+        fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    fn keys_projects_the_same_before_and_after_they_exist() {
+        let dir = temp_dir("key");
+        let project = dir.join("Game");
+        let before = cache_key(&project);
+        fs::create_dir_all(&project).unwrap();
+        assert_eq!(cache_key(&project), before);
+        assert_eq!(cache_key(&dir.join("Game/../Game/.")), before);
+        assert_eq!(before, project.canonicalize().unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
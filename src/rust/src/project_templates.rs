@@ -0,0 +1,341 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Id of the built-in template that `create_project` falls back to.
+pub const DEFAULT_TEMPLATE_ID: &str = "default";
+
+/// Manifest file describing a template. It is never copied into projects.
+const TEMPLATE_MANIFEST: &str = "template.json";
+
+/// Files with these extensions get placeholder substitution when copied.
+const TEXT_EXTENSIONS: &[&str] = &["json", "ts", "tsx", "js", "mjs", "html", "css", "md", "txt"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub path: String,
+    pub builtin: bool,
+}
+
+/// A template directory that couldn't be read, e.g. because of a malformed
+/// `template.json`.
+#[derive(Debug, Serialize)]
+pub struct InvalidTemplate {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TemplateList {
+    pub templates: Vec<TemplateInfo>,
+    /// Templates left out of `templates`.
+    pub invalid: Vec<InvalidTemplate>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TemplateManifest {
+    name: Option<String>,
+    description: Option<String>,
+}
+
+/// Values substituted into template files.
+///
+/// `{{PROJECT_NAME}}` is the display name, `{{PROJECT_ID}}` the sanitized
+/// directory name.
+pub struct TemplateVars<'a> {
+    pub project_name: &'a str,
+    pub project_id: &'a str,
+}
+
+impl TemplateVars<'_> {
+    fn apply(&self, content: &str, escape_json: bool) -> String {
+        let escape = |value: &str| {
+            if escape_json {
+                let quoted = serde_json::Value::String(value.to_string()).to_string();
+                quoted[1..quoted.len() - 1].to_string()
+            } else {
+                value.to_string()
+            }
+        };
+        content
+            .replace("{{PROJECT_NAME}}", &escape(self.project_name))
+            .replace("{{PROJECT_ID}}", &escape(self.project_id))
+    }
+}
+
+/// Looks up project templates in the bundled templates directory and in the
+/// user's templates directory. User templates shadow bundled ones with the
+/// same id.
+pub struct TemplateRegistry {
+    bundled_dir: Option<PathBuf>,
+    user_dir: Option<PathBuf>,
+}
+
+impl TemplateRegistry {
+    /// `resource_dir` is the app's resource directory, where release builds
+    /// ship the bundled templates.
+    pub fn new(resource_dir: Option<PathBuf>, user_dir: Option<PathBuf>) -> Self {
+        TemplateRegistry {
+            bundled_dir: Self::get_bundled_templates_directory(resource_dir).ok(),
+            user_dir,
+        }
+    }
+
+    fn get_bundled_templates_directory(resource_dir: Option<PathBuf>) -> Result<PathBuf, String> {
+        if let Ok(env_path) = std::env::var("THREE_ENGINE_TEMPLATES_DIR") {
+            let path = PathBuf::from(env_path);
+            if path.is_absolute() {
+                return Ok(path);
+            }
+        }
+
+        if let Some(path) = resource_dir.map(|dir| dir.join("templates")).filter(|path| path.is_dir()) {
+            return Ok(path);
+        }
+
+        Ok(crate::project_manager::get_base_directory()?.join("templates"))
+    }
+
+    /// Lists the available templates. A template that can't be read is
+    /// reported in `invalid` rather than failing the whole list.
+    pub fn list_templates(&self) -> Result<TemplateList, String> {
+        let mut invalid = Vec::new();
+        let mut templates = vec![TemplateInfo {
+            id: DEFAULT_TEMPLATE_ID.to_string(),
+            name: "Empty Project".to_string(),
+            description: "A project with an empty Main scene".to_string(),
+            path: String::new(),
+            builtin: true,
+        }];
+
+        for (dir, builtin) in [(&self.bundled_dir, true), (&self.user_dir, false)] {
+            let Some(dir) = dir else { continue };
+            if !dir.is_dir() {
+                continue;
+            }
+
+            let entries = match fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    invalid.push(InvalidTemplate {
+                        path: dir.to_string_lossy().to_string(),
+                        error: format!("Failed to read templates directory: {}", e),
+                    });
+                    continue;
+                }
+            };
+
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if !path.is_dir() {
+                    continue;
+                }
+
+                match Self::read_template_info(&path, builtin) {
+                    Ok(info) => {
+                        templates.retain(|t| t.id != info.id);
+                        templates.push(info);
+                    }
+                    Err(error) => invalid.push(InvalidTemplate {
+                        path: path.to_string_lossy().to_string(),
+                        error,
+                    }),
+                }
+            }
+        }
+
+        templates.sort_by(|a, b| {
+            let a_default = a.id == DEFAULT_TEMPLATE_ID;
+            let b_default = b.id == DEFAULT_TEMPLATE_ID;
+            b_default.cmp(&a_default).then_with(|| a.name.cmp(&b.name))
+        });
+
+        Ok(TemplateList { templates, invalid })
+    }
+
+    /// Resolves a template id to its directory. Returns `None` for the
+    /// built-in default template, which has no directory.
+    pub fn find_template(&self, id: &str) -> Result<Option<PathBuf>, String> {
+        if id.is_empty() || id == DEFAULT_TEMPLATE_ID {
+            return Ok(None);
+        }
+
+        if sanitize_filename::sanitize(id) != id {
+            return Err(format!("Invalid template id '{}'", id));
+        }
+
+        for dir in [&self.user_dir, &self.bundled_dir].into_iter().flatten() {
+            let path = dir.join(id);
+            if path.is_dir() {
+                return Ok(Some(path));
+            }
+        }
+
+        Err(format!("Template '{}' not found", id))
+    }
+
+    fn read_template_info(path: &Path, builtin: bool) -> Result<TemplateInfo, String> {
+        let id = path.file_name()
+            .and_then(|n| n.to_str())
+            .ok_or("Invalid template path")?
+            .to_string();
+
+        let manifest_path = path.join(TEMPLATE_MANIFEST);
+        let manifest = if manifest_path.exists() {
+            let content = fs::read_to_string(&manifest_path)
+                .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+            serde_json::from_str::<TemplateManifest>(&content)
+                .map_err(|e| format!("Invalid template manifest {}: {}", manifest_path.display(), e))?
+        } else {
+            TemplateManifest::default()
+        };
+
+        Ok(TemplateInfo {
+            name: manifest.name.unwrap_or_else(|| id.clone()),
+            description: manifest.description.unwrap_or_default(),
+            id,
+            path: path.to_string_lossy().to_string(),
+            builtin,
+        })
+    }
+}
+
+/// Copies a template directory into `dest`, substituting placeholders in text
+/// files. Existing files in `dest` are overwritten.
+pub fn instantiate_template(template_dir: &Path, dest: &Path, vars: &TemplateVars) -> Result<(), String> {
+    copy_template_dir(template_dir, dest, vars, true)
+}
+
+fn copy_template_dir(src: &Path, dst: &Path, vars: &TemplateVars, is_root: bool) -> Result<(), String> {
+    fs::create_dir_all(dst)
+        .map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    let entries = fs::read_dir(src)
+        .map_err(|e| format!("Failed to read template directory: {}", e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+
+        if is_root && file_name == TEMPLATE_MANIFEST {
+            continue;
+        }
+
+        let dst_path = dst.join(&file_name);
+
+        if path.is_dir() {
+            copy_template_dir(&path, &dst_path, vars, false)?;
+            continue;
+        }
+
+        let extension = path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .unwrap_or_default();
+
+        if TEXT_EXTENSIONS.contains(&extension.as_str()) {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read template file {}: {}", path.display(), e))?;
+            fs::write(&dst_path, vars.apply(&content, extension == "json"))
+                .map_err(|e| format!("Failed to write {}: {}", dst_path.display(), e))?;
+        } else {
+            fs::copy(&path, &dst_path)
+                .map_err(|e| format!("Failed to copy {}: {}", path.display(), e))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("project-templates-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn template(dir: &Path, id: &str, manifest: &str) {
+        fs::create_dir_all(dir.join(id)).unwrap();
+        fs::write(dir.join(id).join(TEMPLATE_MANIFEST), manifest).unwrap();
+    }
+
+    #[test]
+    fn lists_templates_with_user_ones_shadowing_bundled_ones() {
+        let dir = temp_dir("list");
+        let (bundled, user) = (dir.join("bundled"), dir.join("user"));
+        template(&bundled, "platformer", r#"{ "name": "Platformer" }"#);
+        template(&bundled, "arena", r#"{ "name": "Arena", "description": "Bundled" }"#);
+        template(&user, "arena", r#"{ "name": "Arena", "description": "Mine" }"#);
+        template(&user, "broken", "{ not json");
+
+        let registry = TemplateRegistry { bundled_dir: Some(bundled.clone()), user_dir: Some(user.clone()) };
+        let list = registry.list_templates().unwrap();
+        let ids: Vec<_> = list.templates.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["default", "arena", "platformer"]);
+        assert_eq!(list.templates[1].description, "Mine");
+        assert!(!list.templates[1].builtin);
+        assert_eq!(list.invalid.len(), 1);
+        assert!(list.invalid[0].path.ends_with("broken"));
+
+        assert_eq!(registry.find_template("arena").unwrap(), Some(user.join("arena")));
+        assert_eq!(registry.find_template(DEFAULT_TEMPLATE_ID).unwrap(), None);
+        assert!(registry.find_template("../user/arena").is_err());
+        assert!(registry.find_template("missing").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn substitutes_placeholders_when_instantiating() {
+        let dir = temp_dir("instantiate");
+        let template_dir = dir.join("template");
+        template(&dir, "template", r#"{ "name": "Template" }"#);
+        fs::create_dir_all(template_dir.join("scenes")).unwrap();
+        fs::write(template_dir.join("scenes/Main.json"), r#"{ "title": "{{PROJECT_NAME}}" }"#).unwrap();
+        fs::write(template_dir.join("README.md"), "# {{PROJECT_NAME}} ({{PROJECT_ID}})").unwrap();
+        fs::write(template_dir.join("logo.png"), "{{PROJECT_NAME}}").unwrap();
+
+        let dest = dir.join("project");
+        let vars = TemplateVars { project_name: "My \"Game\"", project_id: "My_Game" };
+        instantiate_template(&template_dir, &dest, &vars).unwrap();
+
+        let scene: serde_json::Value = serde_json::from_str(&fs::read_to_string(dest.join("scenes/Main.json")).unwrap()).unwrap();
+        assert_eq!(scene["title"], "My \"Game\"");
+        assert_eq!(fs::read_to_string(dest.join("README.md")).unwrap(), "# My \"Game\" (My_Game)");
+        // Binary files are copied as they are.
+        assert_eq!(fs::read_to_string(dest.join("logo.png")).unwrap(), "{{PROJECT_NAME}}");
+        assert!(!dest.join(TEMPLATE_MANIFEST).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn bundled_templates_instantiate_to_valid_scenes() {
+        let bundled = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../templates");
+        let registry = TemplateRegistry { bundled_dir: Some(bundled), user_dir: None };
+        let list = registry.list_templates().unwrap();
+        assert!(list.invalid.is_empty());
+
+        let dir = temp_dir("bundled");
+        let vars = TemplateVars { project_name: "Game", project_id: "Game" };
+        for template in list.templates.iter().filter(|t| t.id != DEFAULT_TEMPLATE_ID) {
+            let dest = dir.join(&template.id);
+            instantiate_template(Path::new(&template.path), &dest, &vars).unwrap();
+            for scene_name in crate::scene_store::list_scene_names(&dest) {
+                let content = crate::scene_store::read_scene(&dest, &scene_name).unwrap();
+                crate::scene::SceneDocument::from_json_str(&content)
+                    .unwrap_or_else(|e| panic!("{}/{}: {}", template.id, scene_name, e));
+            }
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
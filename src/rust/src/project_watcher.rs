@@ -0,0 +1,353 @@
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::project_metadata::PROJECT_FILE;
+
+/// Quiet time after the last change before a batch is reported.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Longest a change is held back while others keep arriving.
+const MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// How long after the backend wrote a path changes to it are taken to be
+/// its own.
+const OWN_WRITE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Directories of a project changes are reported for, along with
+/// `project.json`.
+const WATCHED_DIRS: &[&str] = &["scenes", "assets"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileChange {
+    pub kind: ChangeKind,
+    /// Relative to the project, with `/` separators, e.g. `scenes/Main.json`.
+    pub path: String,
+    /// Previous path of a renamed file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+}
+
+impl FileChange {
+    fn new(kind: ChangeKind, path: String) -> Self {
+        FileChange { kind, path, from: None }
+    }
+}
+
+/// Paths the backend wrote recently, so the watcher doesn't report them back
+/// to the editor as external changes.
+static OWN_WRITES: Mutex<Vec<(PathBuf, Instant)>> = Mutex::new(Vec::new());
+
+/// Records that the backend is about to write `path`. A directory covers
+/// everything below it. Call it before writing: changes are checked against
+/// it as the watcher receives them.
+pub fn note_own_write(path: &Path) {
+    let mut writes = OWN_WRITES.lock().unwrap_or_else(|e| e.into_inner());
+    writes.retain(|(written, at)| at.elapsed() < OWN_WRITE_WINDOW && written != path);
+    writes.push((path.to_path_buf(), Instant::now()));
+}
+
+fn is_own_write(path: &Path) -> bool {
+    OWN_WRITES.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .any(|(written, at)| at.elapsed() < OWN_WRITE_WINDOW && path.starts_with(written))
+}
+
+/// Watches a project for changes made outside the editor, e.g. assets saved
+/// from Blender or scripts edited in an IDE. Stops when dropped.
+pub struct ProjectWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl ProjectWatcher {
+    /// Starts watching `project_path`. `on_changes` is called on the
+    /// watcher's thread with each debounced batch of changes.
+    pub fn start(project_path: &Path, on_changes: impl Fn(Vec<FileChange>) + Send + 'static) -> Result<Self, String> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)
+            .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+
+        // The whole project, as `scenes/` and `assets/` may only be created
+        // later. Everything else is filtered out by `is_watched`.
+        watcher.watch(project_path, RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch project: {}", e))?;
+
+        let root = project_path.to_path_buf();
+        // Some platforms report events under the canonical path.
+        let canonical_root = root.canonicalize().unwrap_or_else(|_| root.clone());
+
+        std::thread::spawn(move || {
+            let relative = |path: &Path| -> Option<String> {
+                let relative = path.strip_prefix(&root)
+                    .or_else(|_| path.strip_prefix(&canonical_root))
+                    .ok()?;
+                is_watched(relative).then(|| relative_string(relative))
+            };
+
+            let mut batch = Batch::default();
+            loop {
+                match rx.recv_timeout(DEBOUNCE) {
+                    // Own writes are told apart on arrival, as a batch may be
+                    // held back for as long as they are remembered.
+                    Ok(Ok(event)) => {
+                        for change in changes_of(&event, &relative) {
+                            let own = is_own_write(&root.join(&change.path))
+                                || change.from.as_ref().is_some_and(|from| is_own_write(&root.join(from)));
+                            if !own {
+                                batch.push(change);
+                            }
+                        }
+                    }
+                    Ok(Err(e)) => log::warn!("[Watcher] Error: {:?}", e),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    // The watcher was dropped.
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+
+                if batch.is_due() {
+                    let changes = batch.take();
+                    if !changes.is_empty() {
+                        on_changes(changes);
+                    }
+                }
+            }
+        });
+
+        Ok(ProjectWatcher { _watcher: watcher })
+    }
+}
+
+/// `project.json` and anything in the watched directories, except hidden
+/// entries such as the temp files of atomic writes.
+fn is_watched(relative: &Path) -> bool {
+    let mut components = relative.components();
+    let first = match components.next() {
+        Some(Component::Normal(first)) => first,
+        _ => return false,
+    };
+
+    let hidden = relative.components().any(|component| {
+        component.as_os_str().to_str().map_or(true, |name| name.starts_with('.') || name.ends_with('~'))
+    });
+    if hidden {
+        return false;
+    }
+
+    match components.next() {
+        None => first == PROJECT_FILE,
+        Some(_) => WATCHED_DIRS.iter().any(|dir| first == *dir),
+    }
+}
+
+fn relative_string(relative: &Path) -> String {
+    relative.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The changes a notify event stands for. Paths outside what is watched are
+/// dropped; a rename from or to such a path becomes a plain change.
+fn changes_of(event: &Event, relative: &impl Fn(&Path) -> Option<String>) -> Vec<FileChange> {
+    let paths = || event.paths.iter().filter_map(|path| relative(path));
+    let all = |kind: ChangeKind| paths().map(|path| FileChange::new(kind, path)).collect();
+
+    match event.kind {
+        EventKind::Create(_) => all(ChangeKind::Created),
+        EventKind::Remove(_) => all(ChangeKind::Removed),
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => all(ChangeKind::Removed),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => all(ChangeKind::Created),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            let from = event.paths.first().and_then(|path| relative(path));
+            let to = event.paths.get(1).and_then(|path| relative(path));
+            match (from, to) {
+                (Some(from), Some(path)) => vec![FileChange { kind: ChangeKind::Renamed, path, from: Some(from) }],
+                (None, Some(path)) => vec![FileChange::new(ChangeKind::Modified, path)],
+                (Some(from), None) => vec![FileChange::new(ChangeKind::Removed, from)],
+                (None, None) => Vec::new(),
+            }
+        }
+        // Platforms that can't tell which side of a rename a path is on.
+        EventKind::Modify(ModifyKind::Name(_)) => event.paths.iter()
+            .filter_map(|path| {
+                let kind = if path.exists() { ChangeKind::Created } else { ChangeKind::Removed };
+                Some(FileChange::new(kind, relative(path)?))
+            })
+            .collect(),
+        EventKind::Modify(ModifyKind::Metadata(_)) => Vec::new(),
+        EventKind::Modify(_) => all(ChangeKind::Modified),
+        _ => Vec::new(),
+    }
+}
+
+/// Changes collected since the last report, at most one per path.
+#[derive(Default)]
+struct Batch {
+    changes: Vec<FileChange>,
+    first: Option<Instant>,
+    last: Option<Instant>,
+}
+
+impl Batch {
+    fn push(&mut self, change: FileChange) {
+        let now = Instant::now();
+        self.first.get_or_insert(now);
+        self.last = Some(now);
+
+        if change.kind == ChangeKind::Renamed {
+            // The two halves of a rename may have been reported already.
+            let from = change.from.clone().unwrap_or_default();
+            let was_created = self.remove(&from).is_some_and(|c| c.kind == ChangeKind::Created);
+            self.remove(&change.path);
+            if was_created {
+                self.changes.push(FileChange::new(ChangeKind::Created, change.path));
+            } else {
+                self.changes.push(change);
+            }
+            return;
+        }
+
+        let merged = match self.remove(&change.path) {
+            None => Some(change),
+            Some(previous) => match (previous.kind, change.kind) {
+                (ChangeKind::Created, ChangeKind::Removed) => None,
+                (ChangeKind::Created, _) => Some(previous),
+                (ChangeKind::Removed, ChangeKind::Created) => Some(FileChange::new(ChangeKind::Modified, change.path)),
+                (ChangeKind::Renamed, ChangeKind::Modified) => Some(previous),
+                (ChangeKind::Renamed, ChangeKind::Removed) => previous.from.map(|from| FileChange::new(ChangeKind::Removed, from)),
+                _ => Some(change),
+            },
+        };
+        self.changes.extend(merged);
+    }
+
+    fn remove(&mut self, path: &str) -> Option<FileChange> {
+        let index = self.changes.iter().position(|change| change.path == path)?;
+        Some(self.changes.remove(index))
+    }
+
+    fn is_due(&self) -> bool {
+        match (self.first, self.last) {
+            (Some(first), Some(last)) => last.elapsed() >= DEBOUNCE || first.elapsed() >= MAX_DELAY,
+            _ => false,
+        }
+    }
+
+    fn take(&mut self) -> Vec<FileChange> {
+        self.first = None;
+        self.last = None;
+        std::mem::take(&mut self.changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, RemoveKind};
+
+    fn change(kind: ChangeKind, path: &str) -> FileChange {
+        FileChange::new(kind, path.to_string())
+    }
+
+    fn renamed(from: &str, path: &str) -> FileChange {
+        FileChange { kind: ChangeKind::Renamed, path: path.to_string(), from: Some(from.to_string()) }
+    }
+
+    fn batch(changes: Vec<FileChange>) -> Vec<FileChange> {
+        let mut batch = Batch::default();
+        for change in changes {
+            batch.push(change);
+        }
+        batch.take()
+    }
+
+    #[test]
+    fn watches_scenes_assets_and_project_file() {
+        assert!(is_watched(Path::new("project.json")));
+        assert!(is_watched(Path::new("scenes/Main.json")));
+        assert!(is_watched(Path::new("assets/models/tree.glb")));
+        assert!(!is_watched(Path::new("build/index.html")));
+        assert!(!is_watched(Path::new("notes.txt")));
+        assert!(!is_watched(Path::new("assets/.tree.glb.tmp")));
+        assert!(!is_watched(Path::new("scenes/Main.json~")));
+        assert!(!is_watched(Path::new(".recovery/Main.json")));
+    }
+
+    #[test]
+    fn keeps_one_change_per_path() {
+        assert_eq!(
+            batch(vec![change(ChangeKind::Created, "assets/a.png"), change(ChangeKind::Modified, "assets/a.png")]),
+            vec![change(ChangeKind::Created, "assets/a.png")]
+        );
+        assert_eq!(
+            batch(vec![change(ChangeKind::Created, "assets/a.png"), change(ChangeKind::Removed, "assets/a.png")]),
+            Vec::new()
+        );
+        // Atomic saves of other tools replace the file.
+        assert_eq!(
+            batch(vec![change(ChangeKind::Removed, "assets/a.png"), change(ChangeKind::Created, "assets/a.png")]),
+            vec![change(ChangeKind::Modified, "assets/a.png")]
+        );
+    }
+
+    #[test]
+    fn coalesces_renames() {
+        // A file created and then renamed is just created under its new name.
+        assert_eq!(
+            batch(vec![change(ChangeKind::Created, "assets/a.png"), renamed("assets/a.png", "assets/b.png")]),
+            vec![change(ChangeKind::Created, "assets/b.png")]
+        );
+        assert_eq!(
+            batch(vec![renamed("assets/a.png", "assets/b.png"), change(ChangeKind::Modified, "assets/b.png")]),
+            vec![renamed("assets/a.png", "assets/b.png")]
+        );
+        // Renamed away and then removed, the file is gone from its old path.
+        assert_eq!(
+            batch(vec![renamed("assets/a.png", "assets/b.png"), change(ChangeKind::Removed, "assets/b.png")]),
+            vec![change(ChangeKind::Removed, "assets/a.png")]
+        );
+    }
+
+    #[test]
+    fn maps_events_to_watched_changes() {
+        let root = Path::new("/project");
+        let relative = |path: &Path| -> Option<String> {
+            let relative = path.strip_prefix(root).ok()?;
+            is_watched(relative).then(|| relative_string(relative))
+        };
+        let event = |kind: EventKind, paths: &[&str]| {
+            paths.iter().fold(Event::new(kind), |event, path| event.add_path(root.join(path)))
+        };
+
+        let created = event(EventKind::Create(CreateKind::File), &["assets/a.png", "build/a.png"]);
+        assert_eq!(changes_of(&created, &relative), vec![change(ChangeKind::Created, "assets/a.png")]);
+
+        let removed = event(EventKind::Remove(RemoveKind::File), &["scenes/Main.json"]);
+        assert_eq!(changes_of(&removed, &relative), vec![change(ChangeKind::Removed, "scenes/Main.json")]);
+
+        let modified = event(EventKind::Modify(ModifyKind::Data(DataChange::Content)), &["project.json"]);
+        assert_eq!(changes_of(&modified, &relative), vec![change(ChangeKind::Modified, "project.json")]);
+
+        let rename = |from: &str, to: &str| {
+            changes_of(&event(EventKind::Modify(ModifyKind::Name(RenameMode::Both)), &[from, to]), &relative)
+        };
+        assert_eq!(rename("assets/a.png", "assets/b.png"), vec![renamed("assets/a.png", "assets/b.png")]);
+        // Atomic writes rename a hidden temp file over the target.
+        assert_eq!(rename("assets/.a.png.tmp", "assets/a.png"), vec![change(ChangeKind::Modified, "assets/a.png")]);
+        assert_eq!(rename("assets/a.png", "notes/a.png"), vec![change(ChangeKind::Removed, "assets/a.png")]);
+    }
+}
@@ -0,0 +1,141 @@
+use flate2::Crc;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::project_metadata::PROJECT_FILE;
+use crate::scene_store;
+
+/// Prefix of the error returned when a file changed on disk since the
+/// editor read it, e.g. after a `git pull`.
+pub const CONFLICT_ERROR_PREFIX: &str = "CONFLICT: ";
+
+/// File content together with the revision it was read at.
+#[derive(Debug, Serialize)]
+pub struct Revisioned {
+    pub content: String,
+    /// `None` if the file doesn't exist yet.
+    pub revision: Option<String>,
+}
+
+/// Identifies a version of some content. Revisions are only compared, never
+/// interpreted.
+pub fn revision_of(content: &[u8]) -> String {
+    let mut crc = Crc::new();
+    crc.update(content);
+    format!("{:x}-{:08x}", content.len(), crc.sum())
+}
+
+/// Revision of a scene as `scene_store::read_scene` returns it, so a split
+/// scene, spread over several files, has a single one. Converting a scene
+/// to another layout changes its revision.
+pub fn scene_revision(project_path: &Path, scene_name: &str) -> Result<Option<String>, String> {
+    if !scene_store::scene_exists(project_path, scene_name) {
+        return Ok(None);
+    }
+    let content = scene_store::read_scene(project_path, scene_name)?;
+    Ok(Some(revision_of(content.as_bytes())))
+}
+
+/// Revision of `project.json`, or `None` if there is none.
+pub fn project_config_revision(project_path: &Path) -> Result<Option<String>, String> {
+    match fs::read(project_path.join(PROJECT_FILE)) {
+        Ok(content) => Ok(Some(project_config_revision_of(&content))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read project config: {}", e)),
+    }
+}
+
+/// Revision of `project.json` content. Its `modified` time is left out:
+/// every scene save updates it, and that alone shouldn't count as a
+/// conflicting change to the settings.
+pub fn project_config_revision_of(content: &[u8]) -> String {
+    let normalized = serde_json::from_slice::<serde_json::Value>(content)
+        .ok()
+        .and_then(|mut value| {
+            value.as_object_mut()?.remove("modified");
+            serde_json::to_vec(&value).ok()
+        });
+    revision_of(normalized.as_deref().unwrap_or(content))
+}
+
+/// Fails with a conflict if `current` is not the revision the caller last
+/// read. Without an expected revision, any state is accepted.
+pub fn check_revision(what: &str, current: Option<&str>, expected: Option<&str>) -> Result<(), String> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+
+    match current {
+        Some(current) if current == expected => Ok(()),
+        Some(_) => Err(format!("{}{} changed on disk since it was read", CONFLICT_ERROR_PREFIX, what)),
+        None => Err(format!("{}{} was deleted since it was read", CONFLICT_ERROR_PREFIX, what)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_metadata::{ProjectMetadata, SceneStorage};
+    use std::path::PathBuf;
+
+    const SCENE: &str = r#"{"camera":{"object":{"type":"PerspectiveCamera"}},"scene":{"object":{"type":"Scene","name":"Level"}}}"#;
+
+    fn temp_project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("revision-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("scenes")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn rejects_stale_revisions() {
+        let project = temp_project("stale");
+        let metadata = ProjectMetadata::new("Game");
+        scene_store::write_scene_for(&metadata, &project, "Main.json", SCENE).unwrap();
+        let read = scene_revision(&project, "Main.json").unwrap();
+        assert!(read.is_some());
+
+        // Changed on disk, e.g. by a git pull.
+        scene_store::write_scene_for(&metadata, &project, "Main.json", &SCENE.replace("Level", "Other")).unwrap();
+        let current = scene_revision(&project, "Main.json").unwrap();
+        let error = check_revision("scenes/Main.json", current.as_deref(), read.as_deref()).unwrap_err();
+        assert!(error.starts_with(CONFLICT_ERROR_PREFIX));
+
+        assert!(check_revision("scenes/Main.json", current.as_deref(), current.as_deref()).is_ok());
+        assert!(check_revision("scenes/Main.json", current.as_deref(), None).is_ok());
+        let error = check_revision("scenes/Main.json", None, current.as_deref()).unwrap_err();
+        assert!(error.starts_with(CONFLICT_ERROR_PREFIX) && error.contains("deleted"));
+
+        fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    fn tracks_split_scenes_as_a_whole() {
+        let project = temp_project("split");
+        let mut metadata = ProjectMetadata::new("Game");
+        metadata.scene_storage = SceneStorage::Split;
+        scene_store::write_scene_for(&metadata, &project, "Main.json", SCENE).unwrap();
+        let read = scene_revision(&project, "Main.json").unwrap();
+        assert!(read.is_some());
+
+        scene_store::write_scene_for(&metadata, &project, "Main.json", SCENE).unwrap();
+        assert_eq!(scene_revision(&project, "Main.json").unwrap(), read);
+        scene_store::write_scene_for(&metadata, &project, "Main.json", &SCENE.replace("Level", "Other")).unwrap();
+        assert_ne!(scene_revision(&project, "Main.json").unwrap(), read);
+
+        assert_eq!(scene_revision(&project, "Missing.json").unwrap(), None);
+        fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    fn project_config_revisions_ignore_the_modified_time() {
+        let a = project_config_revision_of(br#"{"name":"Game","modified":1}"#);
+        let b = project_config_revision_of(br#"{"name":"Game","modified":2}"#);
+        let c = project_config_revision_of(br#"{"name":"Other","modified":1}"#);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(revision_of(b"a"), revision_of(b"b"));
+    }
+}
@@ -0,0 +1,293 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Typed model of a scene file as written by the editor's `toJSON`.
+///
+/// Only the parts the backend checks are typed; everything else is kept in
+/// `extra` so a document round-trips without losing data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneDocument {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<ProjectSettings>,
+    pub camera: ObjectJson,
+    pub scene: ObjectJson,
+    #[serde(default)]
+    pub scripts: BTreeMap<String, Vec<Script>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<History>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Renderer settings stored with the scene. The editor writes whatever its
+/// config holds, so every field is optional.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shadows: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shadow_type: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tone_mapping: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tone_mapping_exposure: Option<f64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Output of three.js `Object3D.toJSON`: the object tree plus the shared
+/// resource tables it references.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectJson {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<JsonMetadata>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub geometries: Vec<Resource>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub materials: Vec<Resource>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub textures: Vec<Resource>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<Resource>,
+    pub object: Object3D,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<f64>,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<String>,
+}
+
+/// An entry of one of the resource tables (geometries, materials, ...).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resource {
+    pub uuid: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Object3D {
+    /// May be empty in freshly created scenes; three.js assigns one on load.
+    #[serde(default)]
+    pub uuid: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// Layer mask. Kept as written: three.js stores it as a signed 32-bit
+    /// value, so `layers.enableAll()` gives `-1`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layers: Option<serde_json::Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matrix: Option<Vec<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub up: Option<Vec<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<MaterialRef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Object3D>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `material` is a single uuid, or one uuid per group for multi-material
+/// meshes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MaterialRef {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Script {
+    #[serde(default)]
+    pub name: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Undo/redo stacks of the editor. Entries are opaque to the backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default)]
+    pub undos: Vec<Value>,
+    #[serde(default)]
+    pub redos: Vec<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl SceneDocument {
+    /// Parses and validates scene file content. Errors name the offending
+    /// location, e.g. `scene.object.children[3].matrix has 15 elements`.
+    pub fn from_json_str(content: &str) -> Result<Self, String> {
+        if content.trim().is_empty() {
+            return Err("Invalid scene: file is empty".to_string());
+        }
+
+        let deserializer = &mut serde_json::Deserializer::from_str(content);
+        let document: SceneDocument = serde_path_to_error::deserialize(deserializer)
+            .map_err(|e| match e.path().to_string().as_str() {
+                "." => format!("Invalid scene: {}", e.inner()),
+                path => format!("Invalid scene: {}: {}", path, e.inner()),
+            })?;
+
+        document.validate()?;
+        Ok(document)
+    }
+
+    /// Checks the constraints serde can't express.
+    pub fn validate(&self) -> Result<(), String> {
+        self.camera.validate("camera")?;
+        self.scene.validate("scene")
+    }
+}
+
+impl ObjectJson {
+    fn validate(&self, path: &str) -> Result<(), String> {
+        for (table, resources) in [
+            ("geometries", &self.geometries),
+            ("materials", &self.materials),
+            ("textures", &self.textures),
+            ("images", &self.images),
+        ] {
+            if let Some(index) = resources.iter().position(|r| r.uuid.is_empty()) {
+                return Err(format!("Invalid scene: {}.{}[{}].uuid is empty", path, table, index));
+            }
+        }
+
+        self.object.validate(&format!("{}.object", path))
+    }
+}
+
+impl Object3D {
+    /// This object and its descendants, depth first, with their hierarchy
+    /// paths, e.g. `Scene/Forest/Tree`. Unnamed objects appear by type.
+    pub fn with_paths(&self) -> Vec<(&Object3D, String)> {
+        fn walk<'a>(object: &'a Object3D, parent_path: &str, out: &mut Vec<(&'a Object3D, String)>) {
+            let label = if object.name.is_empty() { &object.kind } else { &object.name };
+            let path = if parent_path.is_empty() {
+                label.to_string()
+            } else {
+                format!("{}/{}", parent_path, label)
+            };
+
+            out.push((object, path.clone()));
+            for child in &object.children {
+                walk(child, &path, out);
+            }
+        }
+
+        let mut out = Vec::new();
+        walk(self, "", &mut out);
+        out
+    }
+
+    /// Uuids of the materials the object uses, one per material slot.
+    pub fn material_uuids(&self) -> &[String] {
+        match &self.material {
+            Some(MaterialRef::Single(uuid)) => std::slice::from_ref(uuid),
+            Some(MaterialRef::Multiple(uuids)) => uuids,
+            None => &[],
+        }
+    }
+
+    fn validate(&self, path: &str) -> Result<(), String> {
+        if self.kind.is_empty() {
+            return Err(format!("Invalid scene: {}.type is empty", path));
+        }
+
+        for (field, value, len) in [("matrix", &self.matrix, 16), ("up", &self.up, 3)] {
+            if let Some(value) = value {
+                if value.len() != len {
+                    return Err(format!(
+                        "Invalid scene: {}.{} has {} elements, expected {}",
+                        path, field, value.len(), len
+                    ));
+                }
+            }
+        }
+
+        if let Some(MaterialRef::Single(uuid)) = &self.material {
+            if uuid.is_empty() {
+                return Err(format!("Invalid scene: {}.material is empty", path));
+            }
+        }
+
+        for (index, child) in self.children.iter().enumerate() {
+            child.validate(&format!("{}.children[{}]", path, index))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene_with_layers(layers: &str) -> String {
+        format!(
+            r#"{{
+                "camera": {{ "object": {{ "uuid": "c", "type": "PerspectiveCamera", "layers": 1 }} }},
+                "scene": {{ "object": {{ "uuid": "s", "type": "Scene", "layers": {} }} }}
+            }}"#,
+            layers
+        )
+    }
+
+    #[test]
+    fn accepts_any_layer_mask_three_js_writes() {
+        for layers in ["-1", "-2147483648", "4294967295", "1"] {
+            let document = SceneDocument::from_json_str(&scene_with_layers(layers))
+                .unwrap_or_else(|e| panic!("layers {}: {}", layers, e));
+            let json = serde_json::to_value(&document).unwrap();
+            assert_eq!(json["scene"]["object"]["layers"].to_string(), layers);
+        }
+    }
+
+    #[test]
+    fn lists_objects_with_paths_and_materials() {
+        let content = r#"{
+            "camera": { "object": { "type": "PerspectiveCamera" } },
+            "scene": { "object": { "type": "Scene", "name": "Level", "children": [
+                { "type": "Group", "children": [
+                    { "type": "Mesh", "name": "Tree", "material": ["a", "b"] }
+                ] },
+                { "type": "Mesh", "name": "Rock", "material": "c" }
+            ] } }
+        }"#;
+        let document = SceneDocument::from_json_str(content).unwrap();
+        let objects = document.scene.object.with_paths();
+        let paths: Vec<_> = objects.iter().map(|(_, path)| path.as_str()).collect();
+        assert_eq!(paths, ["Level", "Level/Group", "Level/Group/Tree", "Level/Rock"]);
+        assert_eq!(objects[2].0.material_uuids(), ["a", "b"]);
+        assert_eq!(objects[3].0.material_uuids(), ["c"]);
+        assert!(objects[0].0.material_uuids().is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_matrix() {
+        let content = r#"{
+            "camera": { "object": { "type": "PerspectiveCamera" } },
+            "scene": { "object": { "type": "Scene", "matrix": [1, 0, 0] } }
+        }"#;
+        let error = SceneDocument::from_json_str(content).unwrap_err();
+        assert!(error.contains("scene.object.matrix has 3 elements"), "{}", error);
+    }
+}
@@ -0,0 +1,256 @@
+use ciborium::value::{Integer, Value as Cbor};
+use serde_json::{Map, Number, Value};
+
+/// Extension of binary scene files, e.g. `scenes/Main.cbor`.
+pub const BINARY_SCENE_EXTENSION: &str = "cbor";
+
+/// Self-described CBOR tag, so binary scenes can be recognized by their
+/// first bytes.
+const SELF_DESCRIBED_TAG: u64 = 55799;
+
+/// Typed array tags of RFC 8746 (little endian), by the three.js array type
+/// they stand for.
+const TYPED_ARRAYS: &[(&str, u64)] = &[
+    ("Uint8Array", 64),
+    ("Uint8ClampedArray", 68),
+    ("Uint16Array", 69),
+    ("Uint32Array", 70),
+    ("Int8Array", 72),
+    ("Int16Array", 77),
+    ("Int32Array", 78),
+    ("Float32Array", 85),
+    ("Float64Array", 86),
+];
+
+/// Encodes scene JSON as CBOR. The `array` of a BufferGeometry attribute
+/// (`{ "type": "Float32Array", "array": [...] }`) becomes a packed typed
+/// array instead of a list of numbers, unless a value would not survive
+/// the conversion exactly.
+pub fn encode_scene(content: &str) -> Result<Vec<u8>, String> {
+    let value: Value = serde_json::from_str(content)
+        .map_err(|e| format!("Invalid scene: {}", e))?;
+
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(&Cbor::Tag(SELF_DESCRIBED_TAG, Box::new(to_cbor(&value))), &mut bytes)
+        .map_err(|e| format!("Failed to encode scene: {}", e))?;
+    Ok(bytes)
+}
+
+/// Whether `bytes` look like a binary scene rather than JSON, going by the
+/// self-described tag every binary scene starts with.
+pub fn is_binary_scene(bytes: &[u8]) -> bool {
+    bytes.starts_with(&[0xd9, 0xd9, 0xf7])
+}
+
+/// Decodes a binary scene back into (compact) scene JSON.
+pub fn decode_scene(bytes: &[u8]) -> Result<String, String> {
+    let value: Cbor = ciborium::de::from_reader(bytes)
+        .map_err(|e| format!("Invalid binary scene: {}", e))?;
+    serde_json::to_string(&from_cbor(value)?)
+        .map_err(|e| format!("Failed to serialize scene: {}", e))
+}
+
+fn to_cbor(value: &Value) -> Cbor {
+    match value {
+        Value::Null => Cbor::Null,
+        Value::Bool(b) => Cbor::Bool(*b),
+        Value::Number(n) => number_to_cbor(n),
+        Value::String(s) => Cbor::Text(s.clone()),
+        Value::Array(items) => Cbor::Array(items.iter().map(to_cbor).collect()),
+        Value::Object(map) => {
+            let tag = map.get("type")
+                .and_then(Value::as_str)
+                .and_then(|kind| TYPED_ARRAYS.iter().find(|(name, _)| *name == kind))
+                .map(|(_, tag)| *tag);
+
+            Cbor::Map(
+                map.iter()
+                    .map(|(key, value)| {
+                        let packed = match (key.as_str(), tag, value) {
+                            ("array", Some(tag), Value::Array(items)) => pack_typed_array(tag, items),
+                            _ => None,
+                        };
+                        (Cbor::Text(key.clone()), packed.unwrap_or_else(|| to_cbor(value)))
+                    })
+                    .collect(),
+            )
+        }
+    }
+}
+
+fn number_to_cbor(number: &Number) -> Cbor {
+    if let Some(i) = number.as_i64() {
+        Cbor::Integer(i.into())
+    } else if let Some(u) = number.as_u64() {
+        Cbor::Integer(u.into())
+    } else {
+        Cbor::Float(number.as_f64().unwrap_or_default())
+    }
+}
+
+/// Packs numbers into a tagged little-endian byte string, or `None` if any
+/// of them doesn't fit the element type exactly.
+fn pack_typed_array(tag: u64, items: &[Value]) -> Option<Cbor> {
+    fn pack<T, const N: usize>(items: &[Value], convert: impl Fn(f64) -> Option<T>, to_bytes: impl Fn(T) -> [u8; N]) -> Option<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(items.len() * N);
+        for item in items {
+            bytes.extend(to_bytes(convert(item.as_f64()?)?));
+        }
+        Some(bytes)
+    }
+    fn int<T: TryFrom<i64>>(value: f64) -> Option<T> {
+        if value.fract() != 0.0 || value.abs() > 9_007_199_254_740_992.0 {
+            return None;
+        }
+        T::try_from(value as i64).ok()
+    }
+
+    let bytes = match tag {
+        64 | 68 => pack(items, int::<u8>, |v| [v]),
+        69 => pack(items, int::<u16>, u16::to_le_bytes),
+        70 => pack(items, int::<u32>, u32::to_le_bytes),
+        72 => pack(items, int::<i8>, i8::to_le_bytes),
+        77 => pack(items, int::<i16>, i16::to_le_bytes),
+        78 => pack(items, int::<i32>, i32::to_le_bytes),
+        85 => pack(items, |v| Some(v as f32).filter(|f| *f as f64 == v), f32::to_le_bytes),
+        86 => pack(items, Some, f64::to_le_bytes),
+        _ => None,
+    }?;
+
+    Some(Cbor::Tag(tag, Box::new(Cbor::Bytes(bytes))))
+}
+
+fn from_cbor(value: Cbor) -> Result<Value, String> {
+    Ok(match value {
+        Cbor::Null => Value::Null,
+        Cbor::Bool(b) => Value::Bool(b),
+        Cbor::Integer(i) => integer_to_json(i)?,
+        Cbor::Float(f) => float_to_json(f),
+        Cbor::Text(s) => Value::String(s),
+        Cbor::Bytes(bytes) => Value::Array(bytes.into_iter().map(Value::from).collect()),
+        Cbor::Array(items) => Value::Array(items.into_iter().map(from_cbor).collect::<Result<_, _>>()?),
+        Cbor::Map(entries) => {
+            let mut map = Map::new();
+            for (key, value) in entries {
+                let Cbor::Text(key) = key else {
+                    return Err("Invalid binary scene: map key is not a string".to_string());
+                };
+                map.insert(key, from_cbor(value)?);
+            }
+            Value::Object(map)
+        }
+        Cbor::Tag(tag, inner) => match *inner {
+            Cbor::Bytes(bytes) if TYPED_ARRAYS.iter().any(|(_, t)| *t == tag) => unpack_typed_array(tag, &bytes)?,
+            inner => from_cbor(inner)?,
+        },
+        _ => return Err("Invalid binary scene: unsupported value".to_string()),
+    })
+}
+
+fn integer_to_json(integer: Integer) -> Result<Value, String> {
+    let value = i128::from(integer);
+    if let Ok(i) = i64::try_from(value) {
+        Ok(Value::from(i))
+    } else if let Ok(u) = u64::try_from(value) {
+        Ok(Value::from(u))
+    } else {
+        Err("Invalid binary scene: integer out of range".to_string())
+    }
+}
+
+/// Whole floats come back as integers, the way `JSON.stringify` writes
+/// them.
+fn float_to_json(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() <= 9_007_199_254_740_992.0 {
+        Value::from(value as i64)
+    } else {
+        Number::from_f64(value).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn unpack_typed_array(tag: u64, bytes: &[u8]) -> Result<Value, String> {
+    fn unpack<const N: usize>(bytes: &[u8], convert: impl Fn([u8; N]) -> Value) -> Result<Vec<Value>, String> {
+        if bytes.len() % N != 0 {
+            return Err("Invalid binary scene: truncated typed array".to_string());
+        }
+        Ok(bytes.chunks_exact(N)
+            .map(|chunk| convert(chunk.try_into().unwrap()))
+            .collect())
+    }
+
+    let items = match tag {
+        64 | 68 => unpack(bytes, |[b]: [u8; 1]| Value::from(b))?,
+        69 => unpack(bytes, |b| Value::from(u16::from_le_bytes(b)))?,
+        70 => unpack(bytes, |b| Value::from(u32::from_le_bytes(b)))?,
+        72 => unpack(bytes, |b| Value::from(i8::from_le_bytes(b)))?,
+        77 => unpack(bytes, |b| Value::from(i16::from_le_bytes(b)))?,
+        78 => unpack(bytes, |b| Value::from(i32::from_le_bytes(b)))?,
+        85 => unpack(bytes, |b| float_to_json(f32::from_le_bytes(b) as f64))?,
+        86 => unpack(bytes, |b| float_to_json(f64::from_le_bytes(b)))?,
+        _ => return Err(format!("Invalid binary scene: unknown typed array tag {}", tag)),
+    };
+    Ok(Value::Array(items))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(content: &str) -> Value {
+        let bytes = encode_scene(content).unwrap();
+        serde_json::from_str(&decode_scene(&bytes).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn round_trips_plain_values() {
+        let content = r#"{
+            "name": "Level", "visible": true, "userData": null,
+            "position": [1.5, -2, 0.1], "layers": -1, "big": 18446744073709551615,
+            "color": 16777215, "nested": { "list": ["a", { "b": [] }] }
+        }"#;
+        assert_eq!(round_trip(content), serde_json::from_str::<Value>(content).unwrap());
+    }
+
+    #[test]
+    fn writes_whole_floats_as_integers() {
+        // JSON.stringify writes 1.0 as 1; decoding must not turn it into 1.0.
+        let bytes = encode_scene(r#"{ "scale": 1.0, "array": { "type": "Float32Array", "array": [2.0, 0.5] } }"#).unwrap();
+        assert_eq!(decode_scene(&bytes).unwrap(), r#"{"array":{"array":[2,0.5],"type":"Float32Array"},"scale":1}"#);
+    }
+
+    #[test]
+    fn packs_typed_arrays() {
+        let content = r#"{ "attributes": [
+            { "type": "Float32Array", "array": [0, 0.5, -1.25, 3] },
+            { "type": "Float64Array", "array": [0.1, 1e300] },
+            { "type": "Uint8Array", "array": [0, 255] },
+            { "type": "Uint8ClampedArray", "array": [1, 2] },
+            { "type": "Uint16Array", "array": [0, 65535] },
+            { "type": "Uint32Array", "array": [0, 4294967295] },
+            { "type": "Int8Array", "array": [-128, 127] },
+            { "type": "Int16Array", "array": [-32768, 32767] },
+            { "type": "Int32Array", "array": [-2147483648, 2147483647] }
+        ] }"#;
+        assert_eq!(round_trip(content), serde_json::from_str::<Value>(content).unwrap());
+
+        let Cbor::Map(entries) = to_cbor(&serde_json::from_str(r#"{ "type": "Uint16Array", "array": [1, 2] }"#).unwrap()) else {
+            panic!("expected a map");
+        };
+        let array = entries.into_iter().find(|(key, _)| *key == Cbor::Text("array".to_string())).unwrap().1;
+        assert_eq!(array, Cbor::Tag(69, Box::new(Cbor::Bytes(vec![1, 0, 2, 0]))));
+    }
+
+    #[test]
+    fn keeps_values_that_dont_fit_the_typed_array() {
+        // 0.1 has no exact f32, 256 doesn't fit a Uint8Array.
+        let content = r#"{ "a": { "type": "Float32Array", "array": [0.1] }, "b": { "type": "Uint8Array", "array": [256, 1.5] } }"#;
+        assert_eq!(round_trip(content), serde_json::from_str::<Value>(content).unwrap());
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let bytes = encode_scene(r#"{ "type": "Float32Array", "array": [1, 2] }"#).unwrap();
+        assert!(decode_scene(&bytes[..bytes.len() - 1]).is_err());
+        assert!(unpack_typed_array(85, &[0, 0, 0]).is_err());
+    }
+}
@@ -0,0 +1,333 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::scene::{ObjectJson, Resource, SceneDocument};
+
+/// Structural difference between two versions of a scene. Objects and
+/// resources are matched by uuid, so the result does not depend on how the
+/// JSON happens to be laid out.
+#[derive(Debug, Default, Serialize)]
+pub struct SceneDiff {
+    pub added: Vec<ObjectSummary>,
+    pub removed: Vec<ObjectSummary>,
+    pub reparented: Vec<ObjectMove>,
+    pub changed: Vec<ObjectChange>,
+    pub geometries: TableDiff,
+    pub materials: TableDiff,
+    pub textures: TableDiff,
+    pub images: TableDiff,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ObjectSummary {
+    pub uuid: String,
+    pub name: String,
+    pub kind: String,
+}
+
+/// An object that moved to another parent. Parents are given by uuid;
+/// `None` is the root of the camera or scene tree.
+#[derive(Debug, Serialize)]
+pub struct ObjectMove {
+    #[serde(flatten)]
+    pub object: ObjectSummary,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObjectChange {
+    #[serde(flatten)]
+    pub object: ObjectSummary,
+    /// Properties of the object itself that differ, e.g. `matrix` or
+    /// `material`. Changes further down the tree are reported on the
+    /// children.
+    pub fields: Vec<String>,
+}
+
+/// Changes to one of the shared resource tables.
+#[derive(Debug, Default, Serialize)]
+pub struct TableDiff {
+    pub added: Vec<ResourceSummary>,
+    pub removed: Vec<ResourceSummary>,
+    pub changed: Vec<ResourceChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceSummary {
+    pub uuid: String,
+    pub name: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResourceChange {
+    #[serde(flatten)]
+    pub resource: ResourceSummary,
+    pub fields: Vec<String>,
+}
+
+/// An object with its children stripped, as the diff compares it.
+struct FlatObject {
+    summary: ObjectSummary,
+    parent: Option<String>,
+    properties: Map<String, Value>,
+}
+
+/// Objects of the camera and scene trees in tree order. Objects without a
+/// uuid can't be matched between versions and are left out.
+fn flatten(document: &SceneDocument) -> Vec<FlatObject> {
+    fn walk(object: &Value, parent: Option<&str>, out: &mut Vec<FlatObject>) {
+        let Some(map) = object.as_object() else {
+            return;
+        };
+
+        let uuid = text(map, "uuid");
+        if !uuid.is_empty() {
+            let mut properties = map.clone();
+            properties.remove("children");
+            out.push(FlatObject {
+                summary: ObjectSummary {
+                    uuid: uuid.clone(),
+                    name: text(map, "name"),
+                    kind: text(map, "type"),
+                },
+                parent: parent.map(str::to_string),
+                properties,
+            });
+        }
+
+        // Children of an object without a uuid count as children of
+        // whatever it hangs from.
+        let parent = if uuid.is_empty() { parent } else { Some(uuid.as_str()) };
+        if let Some(children) = map.get("children").and_then(Value::as_array) {
+            for child in children {
+                walk(child, parent, out);
+            }
+        }
+    }
+
+    let mut objects = Vec::new();
+    for root in [&document.camera.object, &document.scene.object] {
+        if let Ok(value) = serde_json::to_value(root) {
+            walk(&value, None, &mut objects);
+        }
+    }
+    objects
+}
+
+fn text(map: &Map<String, Value>, key: &str) -> String {
+    map.get(key).and_then(Value::as_str).unwrap_or_default().to_string()
+}
+
+/// Keys whose values differ between the two maps, sorted.
+fn changed_fields(old: &Map<String, Value>, new: &Map<String, Value>) -> Vec<String> {
+    let mut fields: Vec<String> = new.keys()
+        .chain(old.keys())
+        .filter(|key| new.get(*key) != old.get(*key))
+        .cloned()
+        .collect();
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
+pub fn diff_documents(old: &SceneDocument, new: &SceneDocument) -> SceneDiff {
+    let old_objects = flatten(old);
+    let new_objects = flatten(new);
+
+    let old_by_uuid: HashMap<&str, &FlatObject> = old_objects.iter()
+        .map(|object| (object.summary.uuid.as_str(), object))
+        .collect();
+    let new_by_uuid: HashMap<&str, &FlatObject> = new_objects.iter()
+        .map(|object| (object.summary.uuid.as_str(), object))
+        .collect();
+
+    let mut diff = SceneDiff::default();
+
+    for object in &old_objects {
+        if !new_by_uuid.contains_key(object.summary.uuid.as_str()) {
+            diff.removed.push(object.summary.clone());
+        }
+    }
+
+    for object in &new_objects {
+        let Some(previous) = old_by_uuid.get(object.summary.uuid.as_str()) else {
+            diff.added.push(object.summary.clone());
+            continue;
+        };
+
+        if object.parent != previous.parent {
+            diff.reparented.push(ObjectMove {
+                object: object.summary.clone(),
+                from: previous.parent.clone(),
+                to: object.parent.clone(),
+            });
+        }
+
+        let fields = changed_fields(&previous.properties, &object.properties);
+        if !fields.is_empty() {
+            diff.changed.push(ObjectChange {
+                object: object.summary.clone(),
+                fields,
+            });
+        }
+    }
+
+    diff.geometries = diff_table(old, new, |json| &json.geometries);
+    diff.materials = diff_table(old, new, |json| &json.materials);
+    diff.textures = diff_table(old, new, |json| &json.textures);
+    diff.images = diff_table(old, new, |json| &json.images);
+
+    diff
+}
+
+/// Diffs one resource table, merged over the camera and scene trees.
+fn diff_table(old: &SceneDocument, new: &SceneDocument, table: impl Fn(&ObjectJson) -> &Vec<Resource>) -> TableDiff {
+    let entries = |document: &SceneDocument| -> Vec<(ResourceSummary, Map<String, Value>)> {
+        table(&document.camera).iter()
+            .chain(table(&document.scene).iter())
+            .filter_map(|resource| match serde_json::to_value(resource) {
+                Ok(Value::Object(map)) => Some((
+                    ResourceSummary {
+                        uuid: resource.uuid.clone(),
+                        name: text(&map, "name"),
+                        kind: resource.kind.clone().unwrap_or_default(),
+                    },
+                    map,
+                )),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let old_entries = entries(old);
+    let new_entries = entries(new);
+    let old_by_uuid: HashMap<&str, &Map<String, Value>> = old_entries.iter()
+        .map(|(summary, map)| (summary.uuid.as_str(), map))
+        .collect();
+    let new_by_uuid: HashMap<&str, &Map<String, Value>> = new_entries.iter()
+        .map(|(summary, map)| (summary.uuid.as_str(), map))
+        .collect();
+
+    let mut diff = TableDiff::default();
+
+    for (summary, _) in &old_entries {
+        if !new_by_uuid.contains_key(summary.uuid.as_str()) {
+            diff.removed.push(summary.clone());
+        }
+    }
+
+    for (summary, map) in &new_entries {
+        let Some(previous) = old_by_uuid.get(summary.uuid.as_str()) else {
+            diff.added.push(summary.clone());
+            continue;
+        };
+
+        let fields = changed_fields(previous, map);
+        if !fields.is_empty() {
+            diff.changed.push(ResourceChange {
+                resource: summary.clone(),
+                fields,
+            });
+        }
+    }
+
+    diff
+}
+
+/// Diffs two scene files' content.
+pub fn diff_scene_json(old: &str, new: &str) -> Result<SceneDiff, String> {
+    let old = SceneDocument::from_json_str(old)?;
+    let new = SceneDocument::from_json_str(new)?;
+    Ok(diff_documents(&old, &new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = r#"{
+        "camera": { "object": { "uuid": "cam", "type": "PerspectiveCamera" } },
+        "scene": {
+            "geometries": [{ "uuid": "box", "type": "BoxGeometry", "width": 1 }],
+            "materials": [{ "uuid": "red", "type": "MeshBasicMaterial", "color": 16711680 }],
+            "object": { "uuid": "root", "type": "Scene", "children": [
+                { "uuid": "a", "type": "Mesh", "name": "A", "geometry": "box", "material": "red" },
+                { "uuid": "b", "type": "Group", "name": "B", "children": [
+                    { "uuid": "c", "type": "Mesh", "name": "C" }
+                ] },
+                { "uuid": "gone", "type": "PointLight", "name": "Lamp" }
+            ] }
+        }
+    }"#;
+
+    fn uuids(summaries: &[ObjectSummary]) -> Vec<&str> {
+        summaries.iter().map(|s| s.uuid.as_str()).collect()
+    }
+
+    #[test]
+    fn hangs_children_of_uuid_less_objects_from_their_nearest_ancestor() {
+        let document = SceneDocument::from_json_str(r#"{
+            "camera": { "object": { "type": "PerspectiveCamera" } },
+            "scene": { "object": { "uuid": "root", "type": "Scene", "children": [
+                { "type": "Group", "name": "Unnamed", "children": [
+                    { "uuid": "leaf", "type": "Mesh", "name": "Leaf", "children": [] }
+                ] }
+            ] } }
+        }"#).unwrap();
+
+        let objects = flatten(&document);
+        let flat: Vec<(&str, Option<&str>)> = objects.iter()
+            .map(|object| (object.summary.uuid.as_str(), object.parent.as_deref()))
+            .collect();
+        assert_eq!(flat, [("root", None), ("leaf", Some("root"))]);
+        assert!(!objects[1].properties.contains_key("children"));
+        assert_eq!(objects[1].summary.name, "Leaf");
+    }
+
+    #[test]
+    fn reports_each_kind_of_change() {
+        let new = OLD
+            .replace(r#""name": "A","#, r#""name": "A2","#)
+            .replace(r#""width": 1"#, r#""width": 2"#)
+            .replace(
+                r#"{ "uuid": "gone", "type": "PointLight", "name": "Lamp" }"#,
+                r#"{ "uuid": "c", "type": "Mesh", "name": "C" }, { "uuid": "new", "type": "Mesh" }"#,
+            )
+            .replace(
+                r#""children": [
+                    { "uuid": "c", "type": "Mesh", "name": "C" }
+                ] }"#,
+                r#""children": [] }"#,
+            )
+            .replace(r#""materials": [{ "uuid": "red", "type": "MeshBasicMaterial", "color": 16711680 }]"#, r#""materials": []"#);
+
+        let diff = diff_scene_json(OLD, &new).unwrap();
+        assert_eq!(uuids(&diff.added), ["new"]);
+        assert_eq!(uuids(&diff.removed), ["gone"]);
+        assert_eq!(diff.reparented.len(), 1);
+        let moved = &diff.reparented[0];
+        assert_eq!((moved.object.uuid.as_str(), moved.from.as_deref(), moved.to.as_deref()), ("c", Some("b"), Some("root")));
+        // Moving a child doesn't count as a change to its old parent.
+        let changed: Vec<(&str, &[String])> = diff.changed.iter()
+            .map(|change| (change.object.uuid.as_str(), change.fields.as_slice()))
+            .collect();
+        assert_eq!(changed, [("a", &["name".to_string()][..])]);
+
+        assert_eq!(diff.geometries.changed.len(), 1);
+        assert_eq!(diff.geometries.changed[0].fields, ["width"]);
+        assert_eq!(diff.materials.removed.len(), 1);
+        assert_eq!(diff.materials.removed[0].kind, "MeshBasicMaterial");
+    }
+
+    #[test]
+    fn ignores_the_json_layout() {
+        let compact = serde_json::to_string(&serde_json::from_str::<Value>(OLD).unwrap()).unwrap();
+        let diff = diff_scene_json(OLD, &compact).unwrap();
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.reparented.is_empty() && diff.changed.is_empty());
+        assert!(diff.geometries.changed.is_empty() && diff.materials.changed.is_empty());
+        assert!(diff_scene_json(OLD, "{").is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::atomic_write::write_atomic;
+use crate::project_metadata::now_secs;
+use crate::project_metadata_store::ProjectMetadataStore;
+use crate::scene::SceneDocument;
+use crate::scene_diff::{diff_scene_json, SceneDiff};
+use crate::scene_store;
+use crate::undo_history;
+
+/// Local history lives in `<project>/.history/<scene>/`: an `index.json`
+/// plus one gzipped snapshot per save, `<id>.json.gz`.
+pub const HISTORY_DIR: &str = ".history";
+
+/// Snapshots kept per scene; the oldest are dropped first.
+pub const MAX_SNAPSHOTS: usize = 100;
+
+/// Compressed size kept per scene. The latest snapshot is always kept, even
+/// if it alone exceeds this.
+pub const MAX_HISTORY_BYTES: u64 = 64 * 1024 * 1024;
+
+const INDEX_FILE: &str = "index.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub id: u64,
+    pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Size of the scene content.
+    pub size: u64,
+    /// Size of the snapshot on disk.
+    pub compressed_size: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryIndex {
+    #[serde(default)]
+    snapshots: Vec<Snapshot>,
+}
+
+fn history_dir(project_path: &Path, scene_name: &str) -> PathBuf {
+    project_path.join(HISTORY_DIR).join(scene_name)
+}
+
+fn snapshot_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{}.json.gz", id))
+}
+
+/// A missing or unreadable index starts a fresh history rather than blocking
+/// saves.
+fn load_index(dir: &Path) -> HistoryIndex {
+    fs::read_to_string(dir.join(INDEX_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(dir: &Path, index: &HistoryIndex) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(index)
+        .map_err(|e| format!("Failed to serialize history index: {}", e))?;
+    write_atomic(&dir.join(INDEX_FILE), content)
+        .map_err(|e| format!("Failed to write history index: {}", e))
+}
+
+/// Stores `content` as the newest snapshot of a scene and drops the oldest
+/// ones beyond the count and size limits.
+pub fn record_snapshot(project_path: &Path, scene_name: &str, content: &str, label: Option<&str>) -> Result<Snapshot, String> {
+    let dir = history_dir(project_path, scene_name);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create history directory: {}", e))?;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let compressed = encoder.write_all(content.as_bytes())
+        .and_then(|_| encoder.finish())
+        .map_err(|e| format!("Failed to compress snapshot: {}", e))?;
+
+    let mut index = load_index(&dir);
+    let id = index.snapshots.iter().map(|s| s.id).max().map_or(1, |id| id + 1);
+    write_atomic(&snapshot_path(&dir, id), &compressed)
+        .map_err(|e| format!("Failed to write snapshot: {}", e))?;
+
+    let snapshot = Snapshot {
+        id,
+        timestamp: now_secs(),
+        label: label.map(str::trim).filter(|l| !l.is_empty()).map(str::to_string),
+        size: content.len() as u64,
+        compressed_size: compressed.len() as u64,
+    };
+    index.snapshots.push(snapshot.clone());
+    prune(&dir, &mut index);
+    save_index(&dir, &index)?;
+
+    Ok(snapshot)
+}
+
+fn prune(dir: &Path, index: &mut HistoryIndex) {
+    let mut total: u64 = index.snapshots.iter().map(|s| s.compressed_size).sum();
+    while index.snapshots.len() > 1
+        && (index.snapshots.len() > MAX_SNAPSHOTS || total > MAX_HISTORY_BYTES)
+    {
+        let oldest = index.snapshots.remove(0);
+        total -= oldest.compressed_size;
+        let _ = fs::remove_file(snapshot_path(dir, oldest.id));
+    }
+}
+
+/// Lists the snapshots of a scene, most recent first.
+pub fn list_snapshots(project_path: &Path, scene_name: &str) -> Vec<Snapshot> {
+    let mut snapshots = load_index(&history_dir(project_path, scene_name)).snapshots;
+    snapshots.reverse();
+    snapshots
+}
+
+pub fn read_snapshot(project_path: &Path, scene_name: &str, id: u64) -> Result<String, String> {
+    let file = fs::File::open(snapshot_path(&history_dir(project_path, scene_name), id))
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                format!("Snapshot {} of {} not found", id, scene_name)
+            } else {
+                format!("Failed to read snapshot: {}", e)
+            }
+        })?;
+
+    let mut content = String::new();
+    GzDecoder::new(file).read_to_string(&mut content)
+        .map_err(|e| format!("Failed to decompress snapshot {} of {}: {}", id, scene_name, e))?;
+    Ok(content)
+}
+
+/// Diffs snapshot `from` against snapshot `to`, or against the saved scene
+/// when `to` is `None`.
+pub fn diff_snapshots(project_path: &Path, scene_name: &str, from: u64, to: Option<u64>) -> Result<SceneDiff, String> {
+    let old = read_snapshot(project_path, scene_name, from)?;
+    let new = match to {
+        Some(id) => read_snapshot(project_path, scene_name, id)?,
+        None => scene_store::read_scene(project_path, scene_name)?,
+    };
+    diff_scene_json(&old, &new)
+}
+
+/// Replaces a scene with one of its snapshots. The restore is itself
+/// recorded, so it shows up in the history and can be undone. The editor's
+/// undo steps don't apply to the restored version and are dropped.
+pub fn restore_snapshot(metadata_store: &ProjectMetadataStore, project_path: &Path, scene_name: &str, id: u64) -> Result<(), String> {
+    let content = read_snapshot(project_path, scene_name, id)?;
+    SceneDocument::from_json_str(&content)
+        .map_err(|e| format!("Snapshot {} of {} is not usable: {}", id, scene_name, e))?;
+
+    scene_store::write_scene(metadata_store, project_path, scene_name, &content)?;
+    record_snapshot(project_path, scene_name, &content, Some(&format!("Restored snapshot {}", id)))?;
+    undo_history::clear(project_path, scene_name)
+}
+
+/// Moves the history of a renamed scene along with it.
+pub fn rename_history(project_path: &Path, old_name: &str, new_name: &str) {
+    let from = history_dir(project_path, old_name);
+    let to = history_dir(project_path, new_name);
+    if from.exists() {
+        let _ = fs::remove_dir_all(&to);
+        let _ = fs::rename(&from, &to);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_metadata::ProjectMetadata;
+
+    const SCENE: &str = r#"{"camera":{"object":{"type":"PerspectiveCamera"}},"scene":{"object":{"uuid":"root","type":"Scene","name":"Level"}}}"#;
+
+    fn temp_project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("scene-history-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("scenes")).unwrap();
+        dir
+    }
+
+    fn snapshot(id: u64, compressed_size: u64) -> Snapshot {
+        Snapshot { id, timestamp: 0, label: None, size: compressed_size, compressed_size }
+    }
+
+    #[test]
+    fn stores_compressed_snapshots() {
+        let project = temp_project("record");
+        let first = record_snapshot(&project, "Main.json", SCENE, Some("  ")).unwrap();
+        let second = record_snapshot(&project, "Main.json", &SCENE.replace("Level", "Other"), Some(" Before lighting ")).unwrap();
+        assert_eq!((first.id, first.label), (1, None));
+        assert_eq!(second.label.as_deref(), Some("Before lighting"));
+
+        let path = snapshot_path(&history_dir(&project, "Main.json"), 1);
+        assert!(fs::read(&path).unwrap().starts_with(&[0x1f, 0x8b]));
+        assert_eq!(fs::metadata(&path).unwrap().len(), first.compressed_size);
+        assert_eq!(read_snapshot(&project, "Main.json", 1).unwrap(), SCENE);
+        assert!(read_snapshot(&project, "Main.json", 3).unwrap_err().contains("not found"));
+
+        let ids: Vec<u64> = list_snapshots(&project, "Main.json").iter().map(|s| s.id).collect();
+        assert_eq!(ids, [2, 1]);
+        assert!(!diff_snapshots(&project, "Main.json", 1, Some(2)).unwrap().changed.is_empty());
+
+        rename_history(&project, "Main.json", "Level.json");
+        assert!(list_snapshots(&project, "Main.json").is_empty());
+        assert_eq!(read_snapshot(&project, "Level.json", 2).unwrap(), SCENE.replace("Level", "Other"));
+
+        fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    fn keeps_at_most_max_snapshots() {
+        let project = temp_project("count");
+        for _ in 0..MAX_SNAPSHOTS + 2 {
+            record_snapshot(&project, "Main.json", SCENE, None).unwrap();
+        }
+
+        let snapshots = list_snapshots(&project, "Main.json");
+        assert_eq!(snapshots.len(), MAX_SNAPSHOTS);
+        assert_eq!(snapshots.last().unwrap().id, 3);
+        let dir = history_dir(&project, "Main.json");
+        assert!(!snapshot_path(&dir, 2).exists());
+        assert!(snapshot_path(&dir, 3).exists());
+
+        fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    fn drops_the_oldest_snapshots_beyond_the_size_limit() {
+        let dir = temp_project("size");
+        let half = MAX_HISTORY_BYTES / 2;
+        let mut index = HistoryIndex { snapshots: vec![snapshot(1, half), snapshot(2, half), snapshot(3, 1)] };
+        fs::write(snapshot_path(&dir, 1), "").unwrap();
+        prune(&dir, &mut index);
+        assert_eq!(index.snapshots.iter().map(|s| s.id).collect::<Vec<_>>(), [2, 3]);
+        assert!(!snapshot_path(&dir, 1).exists());
+
+        // The latest is kept even on its own over the limit.
+        let mut index = HistoryIndex { snapshots: vec![snapshot(1, 1), snapshot(2, MAX_HISTORY_BYTES + 1)] };
+        prune(&dir, &mut index);
+        assert_eq!(index.snapshots.iter().map(|s| s.id).collect::<Vec<_>>(), [2]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn restores_snapshots_as_a_new_save() {
+        let project = temp_project("restore");
+        ProjectMetadata::new("Game").save(&project).unwrap();
+        let store = ProjectMetadataStore::new();
+        record_snapshot(&project, "Main.json", SCENE, None).unwrap();
+        scene_store::write_scene(&store, &project, "Main.json", &SCENE.replace("Level", "Other")).unwrap();
+        undo_history::save(&project, "Main.json", serde_json::json!({ "undos": [], "redos": [] })).unwrap();
+
+        restore_snapshot(&store, &project, "Main.json", 1).unwrap();
+        assert_eq!(
+            SceneDocument::from_json_str(&scene_store::read_scene(&project, "Main.json").unwrap()).unwrap().scene.object.name,
+            "Level"
+        );
+        let latest = &list_snapshots(&project, "Main.json")[0];
+        assert_eq!((latest.id, latest.label.as_deref()), (2, Some("Restored snapshot 1")));
+        assert_eq!(undo_history::attach(&project, "Main.json", "{}".to_string()).unwrap(), "{}");
+
+        fs::remove_dir_all(&project).unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A project directory, canonicalized where possible, and a scene name.
+type SceneKey = (PathBuf, String);
+
+/// One lock per scene of every project the backend has touched.
+///
+/// Commands that replace a scene run under its lock, so that a save's
+/// revision check and write can't interleave with another change to the
+/// same scene, like `ProjectMetadataStore` does for project.json.
+#[derive(Default)]
+pub struct SceneLocks {
+    scenes: Mutex<HashMap<SceneKey, Arc<Mutex<()>>>>,
+}
+
+impl SceneLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `f` while holding the lock of `scene_name` in the project.
+    pub fn with_lock<R>(&self, project_path: &Path, scene_name: &str, f: impl FnOnce() -> R) -> R {
+        // The same project may be reached through different paths.
+        let project = project_path.canonicalize().unwrap_or_else(|_| project_path.to_path_buf());
+        let lock = self.scenes.lock()
+            .entry((project, scene_name.to_string()))
+            .or_default()
+            .clone();
+
+        let _guard = lock.lock();
+        f()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn serializes_changes_to_a_scene() {
+        let locks = Arc::new(SceneLocks::new());
+        let inside = Arc::new(AtomicUsize::new(0));
+        let project = std::env::temp_dir();
+
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let (locks, inside) = (locks.clone(), inside.clone());
+                // Reached through a different path every other time.
+                let project = if i % 2 == 0 { project.clone() } else { project.join(".") };
+                std::thread::spawn(move || {
+                    locks.with_lock(&project, "Main.json", || {
+                        assert_eq!(inside.fetch_add(1, Ordering::SeqCst), 0);
+                        std::thread::sleep(Duration::from_millis(20));
+                        inside.fetch_sub(1, Ordering::SeqCst);
+                    });
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn locks_scenes_independently() {
+        let locks = SceneLocks::new();
+        let project = std::env::temp_dir();
+        let result = locks.with_lock(&project, "Main.json", || {
+            locks.with_lock(&project, "Level.json", || 42)
+        });
+        assert_eq!(result, 42);
+    }
+}
@@ -0,0 +1,598 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+use crate::scene::SceneDocument;
+
+/// Tables of `Object3D.toJSON` output whose entries are matched by uuid.
+const UUID_TABLES: &[&str] = &["geometries", "materials", "textures", "images", "shapes", "skeletons", "animations"];
+
+/// Key of the root of an object tree. The root is matched by position
+/// rather than uuid, since a fresh scene's root has none.
+const ROOT_KEY: &str = "";
+
+#[derive(Debug, Serialize)]
+pub struct MergeResult {
+    /// The merged scene. Conflicts are resolved in favour of `ours`.
+    pub content: String,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// Both sides changed the same value in different ways.
+    Modified,
+    /// One side deleted what the other side changed.
+    DeleteModify,
+    /// The sides moved an object to different parents, or their moves
+    /// combined would make an object its own ancestor.
+    Parent,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MergeConflict {
+    pub kind: ConflictKind,
+    /// Where in the document the conflict is, e.g.
+    /// `scene.object[<uuid>].matrix` or `scene.materials[<uuid>].color`.
+    pub path: String,
+    /// The object or resource the conflict is about, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// The three versions of the value; `null` where it doesn't exist.
+    pub base: Option<Value>,
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+}
+
+/// Three-way merges scene files. Objects and resources are matched by uuid,
+/// so edits to different objects, or to different properties of the same
+/// object, merge cleanly. Whatever both sides changed is reported as a
+/// conflict and resolved to `ours`.
+pub fn merge_scenes(base: &str, ours: &str, theirs: &str) -> Result<MergeResult, String> {
+    let parse = |content: &str, side: &str| -> Result<Value, String> {
+        SceneDocument::from_json_str(content).map_err(|e| format!("{}: {}", side, e))?;
+        serde_json::from_str(content).map_err(|e| format!("{}: Invalid scene: {}", side, e))
+    };
+    let base = parse(base, "base")?;
+    let ours = parse(ours, "ours")?;
+    let theirs = parse(theirs, "theirs")?;
+
+    let mut merger = Merger::default();
+    let merged = merger.merge_document(&base, &ours, &theirs);
+
+    let document: SceneDocument = serde_json::from_value(merged.clone())
+        .map_err(|e| format!("Merged scene is invalid: {}", e))?;
+    document.validate()
+        .map_err(|e| format!("Merged scene is invalid: {}", e))?;
+
+    let content = serde_json::to_string_pretty(&merged)
+        .map_err(|e| format!("Failed to serialize merged scene: {}", e))?;
+
+    Ok(MergeResult {
+        content,
+        conflicts: merger.conflicts,
+    })
+}
+
+/// Entry point of the `merge-scenes <base> <ours> <theirs>` command, for use
+/// as a git merge driver (`merge-scenes %O %A %B`). The result is written
+/// over `ours` and conflicts are printed as JSON. Exits with 0 on a clean
+/// merge, 1 if there were conflicts and 2 on errors.
+pub fn run_merge_driver(args: &[String]) -> i32 {
+    let [base_path, ours_path, theirs_path] = args else {
+        eprintln!("Usage: merge-scenes <base> <ours> <theirs>");
+        return 2;
+    };
+
+    let read = |path: &String| std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path, e));
+
+    let result = read(base_path)
+        .and_then(|base| Ok((base, read(ours_path)?, read(theirs_path)?)))
+        .and_then(|(base, ours, theirs)| merge_scenes(&base, &ours, &theirs))
+        .and_then(|result| {
+            crate::atomic_write::write_atomic(std::path::Path::new(ours_path), &result.content)
+                .map_err(|e| format!("Failed to write {}: {}", ours_path, e))?;
+            Ok(result)
+        });
+
+    match result {
+        Ok(result) if result.conflicts.is_empty() => 0,
+        Ok(result) => {
+            match serde_json::to_string_pretty(&result.conflicts) {
+                Ok(json) => println!("{}", json),
+                Err(e) => eprintln!("Failed to serialize conflicts: {}", e),
+            }
+            1
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            2
+        }
+    }
+}
+
+/// An object of a flattened tree, without its children.
+#[derive(Clone, PartialEq)]
+struct Node {
+    parent: Option<String>,
+    properties: Map<String, Value>,
+}
+
+/// An object tree keyed by uuid. Objects without one are keyed by their
+/// position under their parent.
+struct Tree {
+    nodes: HashMap<String, Node>,
+    /// Keys in depth-first order, which is also sibling order.
+    order: Vec<String>,
+}
+
+impl Tree {
+    fn new(root: &Value) -> Self {
+        fn walk(object: &Value, key: String, parent: Option<String>, tree: &mut Tree) {
+            let Some(map) = object.as_object() else {
+                return;
+            };
+
+            let mut properties = map.clone();
+            let children = properties.remove("children");
+            tree.order.push(key.clone());
+            tree.nodes.insert(key.clone(), Node { parent, properties });
+
+            for (index, child) in children.iter().filter_map(Value::as_array).flatten().enumerate() {
+                let child_key = match child.get("uuid").and_then(Value::as_str) {
+                    Some(uuid) if !uuid.is_empty() => uuid.to_string(),
+                    _ => format!("{}/{}", key, index),
+                };
+                walk(child, child_key, Some(key.clone()), tree);
+            }
+        }
+
+        let mut tree = Tree { nodes: HashMap::new(), order: Vec::new() };
+        walk(root, ROOT_KEY.to_string(), None, &mut tree);
+        tree
+    }
+}
+
+#[derive(Default)]
+struct Merger {
+    conflicts: Vec<MergeConflict>,
+}
+
+impl Merger {
+    fn conflict(&mut self, kind: ConflictKind, path: String, uuid: Option<&str>, base: Option<&Value>, ours: Option<&Value>, theirs: Option<&Value>) {
+        self.conflicts.push(MergeConflict {
+            kind,
+            path,
+            uuid: uuid.map(str::to_string),
+            base: base.cloned(),
+            ours: ours.cloned(),
+            theirs: theirs.cloned(),
+        });
+    }
+
+    fn merge_document(&mut self, base: &Value, ours: &Value, theirs: &Value) -> Value {
+        let mut merged = Map::new();
+        for key in union_keys(base, ours, theirs) {
+            let (b, o, t) = (base.get(&key), ours.get(&key), theirs.get(&key));
+            let value = match key.as_str() {
+                "camera" | "scene" => match (o, t) {
+                    (Some(o), Some(t)) => Some(self.merge_object_json(&key, b.unwrap_or(&Value::Null), o, t)),
+                    _ => o.cloned(),
+                },
+                // Undo stacks of two sessions can't be combined.
+                "history" => o.cloned(),
+                _ => self.merge_value(key.clone(), None, b, o, t),
+            };
+            if let Some(value) = value {
+                merged.insert(key, value);
+            }
+        }
+        Value::Object(merged)
+    }
+
+    /// Merges the output of one `Object3D.toJSON`: the object tree plus its
+    /// resource tables.
+    fn merge_object_json(&mut self, path: &str, base: &Value, ours: &Value, theirs: &Value) -> Value {
+        let mut merged = Map::new();
+        for key in union_keys(base, ours, theirs) {
+            let (b, o, t) = (base.get(&key), ours.get(&key), theirs.get(&key));
+            let path = format!("{}.{}", path, key);
+            let value = if key == "object" {
+                match (o, t) {
+                    (Some(o), Some(t)) => Some(self.merge_tree(&path, b.unwrap_or(&Value::Null), o, t)),
+                    _ => o.cloned(),
+                }
+            } else if UUID_TABLES.contains(&key.as_str()) {
+                Some(self.merge_table(&path, b, o, t))
+            } else {
+                self.merge_value(path, None, b, o, t)
+            };
+            if let Some(value) = value {
+                merged.insert(key, value);
+            }
+        }
+        Value::Object(merged)
+    }
+
+    fn merge_table(&mut self, path: &str, base: Option<&Value>, ours: Option<&Value>, theirs: Option<&Value>) -> Value {
+        let entries = |table: Option<&Value>| -> Vec<(String, Value)> {
+            table.and_then(Value::as_array).into_iter().flatten()
+                .filter_map(|entry| Some((entry.get("uuid")?.as_str()?.to_string(), entry.clone())))
+                .collect()
+        };
+        let base = entries(base);
+        let ours = entries(ours);
+        let theirs = entries(theirs);
+        let by_uuid = |entries: &[(String, Value)]| -> HashMap<String, Value> { entries.iter().cloned().collect() };
+        let (base_map, ours_map, theirs_map) = (by_uuid(&base), by_uuid(&ours), by_uuid(&theirs));
+
+        let mut seen = HashSet::new();
+        let mut merged = Vec::new();
+        for (uuid, _) in ours.iter().chain(theirs.iter()).chain(base.iter()) {
+            if !seen.insert(uuid.as_str()) {
+                continue;
+            }
+            let entry = self.merge_value(
+                format!("{}[{}]", path, uuid),
+                Some(uuid),
+                base_map.get(uuid),
+                ours_map.get(uuid),
+                theirs_map.get(uuid),
+            );
+            merged.extend(entry);
+        }
+        Value::Array(merged)
+    }
+
+    fn merge_tree(&mut self, path: &str, base: &Value, ours: &Value, theirs: &Value) -> Value {
+        let base = Tree::new(base);
+        let ours = Tree::new(ours);
+        let theirs = Tree::new(theirs);
+
+        let mut seen = HashSet::new();
+        let keys: Vec<&String> = ours.order.iter()
+            .chain(theirs.order.iter())
+            .chain(base.order.iter())
+            .filter(|key| seen.insert(key.as_str()))
+            .collect();
+
+        let mut merged: HashMap<String, Node> = HashMap::new();
+        for key in &keys {
+            let (b, o, t) = (base.nodes.get(*key), ours.nodes.get(*key), theirs.nodes.get(*key));
+            let object_path = if key.is_empty() {
+                path.to_string()
+            } else {
+                format!("{}[{}]", path, key)
+            };
+            let uuid = Some(key.as_str()).filter(|key| !key.is_empty());
+
+            let node = match (o, t) {
+                (Some(o), Some(t)) => {
+                    let properties = self.merge_value(
+                        object_path.clone(),
+                        uuid,
+                        b.map(|b| Value::Object(b.properties.clone())).as_ref(),
+                        Some(&Value::Object(o.properties.clone())),
+                        Some(&Value::Object(t.properties.clone())),
+                    );
+                    let parent = self.merge_parent(&object_path, uuid, b, o, t);
+                    Node {
+                        parent,
+                        properties: match properties {
+                            Some(Value::Object(map)) => map,
+                            _ => o.properties.clone(),
+                        },
+                    }
+                }
+                (Some(o), None) => match b {
+                    None => o.clone(),
+                    Some(b) if b == o => continue,
+                    Some(b) => {
+                        self.conflict(ConflictKind::DeleteModify, object_path, uuid, Some(&node_value(b)), Some(&node_value(o)), None);
+                        o.clone()
+                    }
+                },
+                (None, Some(t)) => match b {
+                    None => t.clone(),
+                    Some(b) if b == t => continue,
+                    Some(b) => {
+                        self.conflict(ConflictKind::DeleteModify, object_path, uuid, Some(&node_value(b)), None, Some(&node_value(t)));
+                        continue;
+                    }
+                },
+                (None, None) => continue,
+            };
+            merged.insert(key.to_string(), node);
+        }
+
+        self.attach_orphans(path, &mut merged, [&ours, &theirs, &base]);
+
+        // Siblings keep ours' order; objects only theirs has follow in
+        // theirs' order.
+        let rank: HashMap<&str, usize> = keys.iter()
+            .enumerate()
+            .map(|(index, key)| (key.as_str(), index))
+            .collect();
+        let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+        for key in &keys {
+            if let Some(parent) = merged.get(key.as_str()).and_then(|node| node.parent.as_deref()) {
+                children.entry(parent).or_default().push(key.as_str());
+            }
+        }
+        for siblings in children.values_mut() {
+            siblings.sort_by_key(|key| rank[key]);
+        }
+
+        build_tree(ROOT_KEY, &merged, &children).unwrap_or_default()
+    }
+
+    fn merge_parent(&mut self, path: &str, uuid: Option<&str>, base: Option<&Node>, ours: &Node, theirs: &Node) -> Option<String> {
+        if ours.parent == theirs.parent || base.is_some_and(|b| b.parent == theirs.parent) {
+            return ours.parent.clone();
+        }
+        if base.is_some_and(|b| b.parent == ours.parent) {
+            return theirs.parent.clone();
+        }
+
+        let parent_value = |node: Option<&Node>| node.map(|n| parent_json(&n.parent));
+        self.conflict(
+            ConflictKind::Parent,
+            format!("{}.parent", path),
+            uuid,
+            parent_value(base).as_ref(),
+            parent_value(Some(ours)).as_ref(),
+            parent_value(Some(theirs)).as_ref(),
+        );
+        ours.parent.clone()
+    }
+
+    /// Reattaches objects whose parent was deleted to the nearest surviving
+    /// ancestor, and breaks cycles made by moves on both sides by moving the
+    /// object to the root. Both are reported as conflicts.
+    fn attach_orphans(&mut self, path: &str, merged: &mut HashMap<String, Node>, [ours, theirs, base]: [&Tree; 3]) {
+        let parent_value = |tree: &Tree, key: &str| {
+            tree.nodes.get(key).map(|node| parent_json(&node.parent))
+        };
+
+        let mut keys: Vec<String> = merged.keys().filter(|key| !key.is_empty()).cloned().collect();
+        keys.sort();
+        for key in &keys {
+            let Some(parent) = merged[key].parent.clone() else {
+                continue;
+            };
+            if merged.contains_key(&parent) {
+                continue;
+            }
+
+            let mut ancestor = Some(parent);
+            let mut visited = HashSet::new();
+            while let Some(candidate) = ancestor.clone() {
+                if merged.contains_key(&candidate) || !visited.insert(candidate.clone()) {
+                    break;
+                }
+                ancestor = [ours, theirs, base].iter()
+                    .find_map(|tree| tree.nodes.get(&candidate))
+                    .and_then(|node| node.parent.clone());
+            }
+
+            self.conflict(
+                ConflictKind::Parent,
+                format!("{}[{}].parent", path, key),
+                Some(key),
+                parent_value(base, key).as_ref(),
+                parent_value(ours, key).as_ref(),
+                parent_value(theirs, key).as_ref(),
+            );
+            merged.get_mut(key).unwrap().parent = Some(
+                ancestor.filter(|a| merged.contains_key(a)).unwrap_or_else(|| ROOT_KEY.to_string())
+            );
+        }
+
+        for key in &keys {
+            let mut visited = HashSet::new();
+            let mut current = key.clone();
+            while let Some(parent) = merged.get(&current).and_then(|node| node.parent.clone()) {
+                if &parent == key || !visited.insert(parent.clone()) {
+                    self.conflict(
+                        ConflictKind::Parent,
+                        format!("{}[{}].parent", path, key),
+                        Some(key),
+                        parent_value(base, key).as_ref(),
+                        parent_value(ours, key).as_ref(),
+                        parent_value(theirs, key).as_ref(),
+                    );
+                    merged.get_mut(key).unwrap().parent = Some(ROOT_KEY.to_string());
+                    break;
+                }
+                current = parent;
+            }
+        }
+    }
+
+    /// Generic three-way merge of a JSON value. Objects are merged key by
+    /// key; anything else changed on both sides is a conflict.
+    fn merge_value(&mut self, path: String, uuid: Option<&str>, base: Option<&Value>, ours: Option<&Value>, theirs: Option<&Value>) -> Option<Value> {
+        if ours == theirs || base == theirs {
+            return ours.cloned();
+        }
+        if base == ours {
+            return theirs.cloned();
+        }
+
+        if let (Some(Value::Object(o)), Some(Value::Object(t))) = (ours, theirs) {
+            let empty = Map::new();
+            let b = match base {
+                Some(Value::Object(b)) => b,
+                _ => &empty,
+            };
+            let mut merged = Map::new();
+            for key in o.keys().chain(t.keys()).chain(b.keys()) {
+                if merged.contains_key(key) {
+                    continue;
+                }
+                let value = self.merge_value(format!("{}.{}", path, key), uuid, b.get(key), o.get(key), t.get(key));
+                if let Some(value) = value {
+                    merged.insert(key.clone(), value);
+                }
+            }
+            return Some(Value::Object(merged));
+        }
+
+        let kind = if ours.is_none() || theirs.is_none() {
+            ConflictKind::DeleteModify
+        } else {
+            ConflictKind::Modified
+        };
+        self.conflict(kind, path, uuid, base, ours, theirs);
+        ours.cloned()
+    }
+}
+
+/// Keys of the three objects, ours' first.
+fn union_keys(base: &Value, ours: &Value, theirs: &Value) -> Vec<String> {
+    let mut seen = HashSet::new();
+    [ours, theirs, base].iter()
+        .filter_map(|value| value.as_object())
+        .flat_map(|map| map.keys())
+        .filter(|key| seen.insert(key.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// A parent in a conflict: its uuid, or `null` for the root of the tree.
+fn parent_json(parent: &Option<String>) -> Value {
+    match parent.as_deref() {
+        None | Some(ROOT_KEY) => Value::Null,
+        Some(parent) => Value::String(parent.to_string()),
+    }
+}
+
+fn node_value(node: &Node) -> Value {
+    Value::Object(node.properties.clone())
+}
+
+fn build_tree(key: &str, nodes: &HashMap<String, Node>, children: &HashMap<&str, Vec<&str>>) -> Option<Value> {
+    let mut object = nodes.get(key)?.properties.clone();
+    let child_values: Vec<Value> = children.get(key).into_iter().flatten()
+        .filter_map(|child| build_tree(child, nodes, children))
+        .collect();
+    if !child_values.is_empty() {
+        object.insert("children".to_string(), Value::Array(child_values));
+    }
+    Some(Value::Object(object))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene(children: &str) -> String {
+        format!(
+            r#"{{
+                "metadata": {{ "version": 4.6, "type": "Object", "generator": "Object3D.toJSON" }},
+                "camera": {{ "object": {{ "uuid": "camera", "type": "PerspectiveCamera" }} }},
+                "scene": {{ "object": {{ "uuid": "scene", "type": "Scene", "children": [{}] }} }}
+            }}"#,
+            children
+        )
+    }
+
+    fn mesh(uuid: &str, name: &str, visible: bool) -> String {
+        format!(r#"{{ "uuid": "{}", "type": "Mesh", "name": "{}", "visible": {} }}"#, uuid, name, visible)
+    }
+
+    fn children(content: &str) -> Vec<Value> {
+        let document: Value = serde_json::from_str(content).unwrap();
+        document["scene"]["object"]["children"].as_array().cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn merges_edits_to_different_objects_and_properties() {
+        let base = scene(&[mesh("a", "A", true), mesh("b", "B", true)].join(","));
+        let ours = scene(&[mesh("a", "Renamed", true), mesh("b", "B", true)].join(","));
+        let theirs = scene(&[mesh("a", "A", false), mesh("b", "B", true), mesh("c", "C", true)].join(","));
+
+        let result = merge_scenes(&base, &ours, &theirs).unwrap();
+        assert!(result.conflicts.is_empty(), "{:?}", result.conflicts);
+
+        let merged = children(&result.content);
+        let uuids: Vec<_> = merged.iter().map(|child| child["uuid"].as_str().unwrap()).collect();
+        assert_eq!(uuids, ["a", "b", "c"]);
+        assert_eq!(merged[0]["name"], "Renamed");
+        assert_eq!(merged[0]["visible"], false);
+    }
+
+    #[test]
+    fn keeps_ours_when_both_sides_change_a_value() {
+        let base = scene(&mesh("a", "A", true));
+        let ours = scene(&mesh("a", "Ours", true));
+        let theirs = scene(&mesh("a", "Theirs", true));
+
+        let result = merge_scenes(&base, &ours, &theirs).unwrap();
+        assert_eq!(children(&result.content)[0]["name"], "Ours");
+
+        let [conflict] = result.conflicts.as_slice() else {
+            panic!("expected one conflict: {:?}", result.conflicts);
+        };
+        assert_eq!(conflict.kind, ConflictKind::Modified);
+        assert_eq!(conflict.uuid.as_deref(), Some("a"));
+        assert_eq!(conflict.ours, Some(Value::from("Ours")));
+        assert_eq!(conflict.theirs, Some(Value::from("Theirs")));
+    }
+
+    #[test]
+    fn reports_deleting_an_object_the_other_side_changed() {
+        let base = scene(&[mesh("a", "A", true), mesh("b", "B", true)].join(","));
+        let ours = scene(&mesh("b", "B", true));
+        let theirs = scene(&[mesh("a", "Changed", true), mesh("b", "B", true)].join(","));
+
+        let result = merge_scenes(&base, &ours, &theirs).unwrap();
+        let [conflict] = result.conflicts.as_slice() else {
+            panic!("expected one conflict: {:?}", result.conflicts);
+        };
+        assert_eq!(conflict.kind, ConflictKind::DeleteModify);
+        assert_eq!(conflict.uuid.as_deref(), Some("a"));
+        assert_eq!(conflict.ours, None);
+
+        let uuids: Vec<_> = children(&result.content).iter()
+            .map(|child| child["uuid"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(uuids, ["b"]);
+    }
+
+    #[test]
+    fn merge_driver_exit_codes() {
+        let dir = std::env::temp_dir().join(format!("scene-merge-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let run = |base: &str, ours: &str, theirs: &str| {
+            let paths = [("base", base), ("ours", ours), ("theirs", theirs)].map(|(name, content)| {
+                let path = dir.join(name);
+                std::fs::write(&path, content).unwrap();
+                path.to_string_lossy().into_owned()
+            });
+            let code = run_merge_driver(&paths);
+            (code, std::fs::read_to_string(dir.join("ours")).unwrap())
+        };
+
+        let base = scene(&mesh("a", "A", true));
+        let (code, merged) = run(&base, &scene(&mesh("a", "Ours", true)), &scene(&mesh("a", "A", false)));
+        assert_eq!(code, 0);
+        assert_eq!(children(&merged)[0]["name"], "Ours");
+        assert_eq!(children(&merged)[0]["visible"], false);
+
+        let (code, merged) = run(&base, &scene(&mesh("a", "Ours", true)), &scene(&mesh("a", "Theirs", true)));
+        assert_eq!(code, 1);
+        assert_eq!(children(&merged)[0]["name"], "Ours");
+
+        let (code, merged) = run(&base, "not a scene", &base);
+        assert_eq!(code, 2);
+        assert_eq!(merged, "not a scene");
+
+        assert_eq!(run_merge_driver(&["only-one".to_string()]), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,277 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::scene::{Object3D, SceneDocument};
+use crate::scene_store;
+
+/// What to look for. Every criterion given must match; names are matched
+/// case-insensitively, with `*` and `?` as wildcards.
+#[derive(Debug, Default, Deserialize)]
+pub struct SceneQuery {
+    /// Object name pattern. Without wildcards, any name containing it
+    /// matches.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Exact object `type`, e.g. `Mesh` or `PointLight`.
+    #[serde(rename = "type", default)]
+    pub kind: Option<String>,
+    /// A key the object's `userData` must have.
+    #[serde(default)]
+    pub user_data_key: Option<String>,
+    /// The value of `user_data_key`, or of any `userData` entry if no key is
+    /// given.
+    #[serde(default)]
+    pub user_data_value: Option<Value>,
+    /// Name pattern of a script attached to the object.
+    #[serde(default)]
+    pub script: Option<String>,
+    /// Uuid of a material the object uses.
+    #[serde(default)]
+    pub material: Option<String>,
+    /// Uuid of a texture the object uses, directly (e.g. a scene background)
+    /// or through one of its materials.
+    #[serde(default)]
+    pub texture: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryHit {
+    pub scene_name: String,
+    pub uuid: String,
+    pub name: String,
+    pub kind: String,
+    /// Names from the scene root down to the object, e.g.
+    /// `Scene/Forest/Tree.003`. Unnamed objects appear by type.
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnreadableScene {
+    pub scene_name: String,
+    pub error: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct QueryResult {
+    pub hits: Vec<QueryHit>,
+    /// Scenes that could not be searched.
+    pub unreadable: Vec<UnreadableScene>,
+}
+
+impl SceneQuery {
+    fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.kind.is_none()
+            && self.user_data_key.is_none()
+            && self.user_data_value.is_none()
+            && self.script.is_none()
+            && self.material.is_none()
+            && self.texture.is_none()
+    }
+}
+
+/// Searches every scene of a project, in name order.
+pub fn query_scenes(project_path: &Path, query: &SceneQuery) -> Result<QueryResult, String> {
+    if query.is_empty() {
+        return Err("Query has no criteria".to_string());
+    }
+
+    let mut result = QueryResult::default();
+    for scene_name in scene_store::list_scene_names(project_path) {
+        let document = scene_store::read_scene(project_path, &scene_name)
+            .and_then(|content| SceneDocument::from_json_str(&content));
+        match document {
+            Ok(document) => result.hits.extend(query_document(&scene_name, &document, query)),
+            Err(error) => result.unreadable.push(UnreadableScene { scene_name, error }),
+        }
+    }
+    Ok(result)
+}
+
+/// Objects of a scene's tree (the root included) that match `query`.
+pub fn query_document(scene_name: &str, document: &SceneDocument, query: &SceneQuery) -> Vec<QueryHit> {
+    // Materials that use the texture, so objects can be matched through them.
+    let texture_materials: HashSet<&str> = match &query.texture {
+        Some(texture) => document.scene.materials.iter()
+            .filter(|material| material.extra.values().any(|value| references(value, texture)))
+            .map(|material| material.uuid.as_str())
+            .collect(),
+        None => HashSet::new(),
+    };
+
+    let context = Context { document, query, texture_materials };
+    document.scene.object.with_paths()
+        .into_iter()
+        .filter(|(object, _)| context.matches(object))
+        .map(|(object, path)| QueryHit {
+            scene_name: scene_name.to_string(),
+            uuid: object.uuid.clone(),
+            name: object.name.clone(),
+            kind: object.kind.clone(),
+            path,
+        })
+        .collect()
+}
+
+struct Context<'a> {
+    document: &'a SceneDocument,
+    query: &'a SceneQuery,
+    texture_materials: HashSet<&'a str>,
+}
+
+impl Context<'_> {
+    fn matches(&self, object: &Object3D) -> bool {
+        let query = self.query;
+
+        if let Some(pattern) = &query.name {
+            if !name_matches(pattern, &object.name) {
+                return false;
+            }
+        }
+
+        if let Some(kind) = &query.kind {
+            if object.kind != *kind {
+                return false;
+            }
+        }
+
+        if query.user_data_key.is_some() || query.user_data_value.is_some() {
+            let user_data = object.extra.get("userData").and_then(Value::as_object);
+            if !user_data.is_some_and(|user_data| user_data_matches(user_data, query)) {
+                return false;
+            }
+        }
+
+        if let Some(pattern) = &query.script {
+            let scripts = self.document.scripts.get(&object.uuid);
+            if !scripts.is_some_and(|scripts| scripts.iter().any(|script| name_matches(pattern, &script.name))) {
+                return false;
+            }
+        }
+
+        if let Some(material) = &query.material {
+            if !object.material_uuids().contains(material) {
+                return false;
+            }
+        }
+
+        if let Some(texture) = &query.texture {
+            let direct = object.extra.iter()
+                .filter(|(key, _)| key.as_str() != "userData")
+                .any(|(_, value)| references(value, texture));
+            if !direct && !object.material_uuids().iter().any(|uuid| self.texture_materials.contains(uuid.as_str())) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+fn user_data_matches(user_data: &Map<String, Value>, query: &SceneQuery) -> bool {
+    match (&query.user_data_key, &query.user_data_value) {
+        (Some(key), Some(value)) => user_data.get(key) == Some(value),
+        (Some(key), None) => user_data.contains_key(key),
+        (None, Some(value)) => user_data.values().any(|v| v == value),
+        (None, None) => true,
+    }
+}
+
+/// Whether `uuid` appears as a string anywhere in `value`. Textures are
+/// referenced from many material properties (`map`, `normalMap`, shader
+/// uniforms, ...), so they are not looked up by key.
+fn references(value: &Value, uuid: &str) -> bool {
+    match value {
+        Value::String(s) => s == uuid,
+        Value::Array(items) => items.iter().any(|item| references(item, uuid)),
+        Value::Object(map) => map.values().any(|item| references(item, uuid)),
+        _ => false,
+    }
+}
+
+/// Case-insensitive match with `*` (any run) and `?` (any one character).
+/// A pattern without wildcards matches anywhere in the name.
+fn name_matches(pattern: &str, name: &str) -> bool {
+    let (pattern, name) = (pattern.to_lowercase(), name.to_lowercase());
+    if !pattern.contains(['*', '?']) {
+        return name.contains(&pattern);
+    }
+
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // Classic wildcard matching, backtracking to the last `*`.
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_names_with_wildcards() {
+        assert!(name_matches("tree", "Big Tree.003"));
+        assert!(name_matches("TREE.*", "Tree.003"));
+        assert!(name_matches("tree.00?", "Tree.003"));
+        assert!(name_matches("*.0*3", "Tree.0103"));
+        assert!(name_matches("*", ""));
+        assert!(!name_matches("tree.?", "Tree.003"));
+        assert!(!name_matches("tree*", "Big Tree"));
+        assert!(!name_matches("rock", "Tree"));
+    }
+
+    #[test]
+    fn finds_objects_by_every_criterion() {
+        let document = SceneDocument::from_json_str(r#"{
+            "camera": { "object": { "type": "PerspectiveCamera" } },
+            "scene": {
+                "materials": [{ "uuid": "bark", "type": "MeshStandardMaterial", "map": "wood" }],
+                "textures": [{ "uuid": "wood" }],
+                "object": { "uuid": "root", "type": "Scene", "name": "Level", "children": [
+                    { "uuid": "t1", "type": "Mesh", "name": "Tree.001", "material": "bark",
+                      "userData": { "tag": "prop" } },
+                    { "uuid": "t2", "type": "Mesh", "name": "Tree.002", "userData": { "tag": "hero" } },
+                    { "uuid": "l1", "type": "PointLight", "name": "Lamp" }
+                ] }
+            },
+            "scripts": { "t2": [{ "name": "Sway.js", "source": "" }] }
+        }"#).unwrap();
+
+        let uuids = |query: SceneQuery| -> Vec<String> {
+            query_document("Main.json", &document, &query).into_iter().map(|hit| hit.uuid).collect()
+        };
+
+        assert_eq!(uuids(SceneQuery { name: Some("tree.*".into()), ..Default::default() }), ["t1", "t2"]);
+        assert_eq!(uuids(SceneQuery { kind: Some("PointLight".into()), ..Default::default() }), ["l1"]);
+        assert_eq!(uuids(SceneQuery { user_data_value: Some("hero".into()), ..Default::default() }), ["t2"]);
+        assert_eq!(uuids(SceneQuery { user_data_key: Some("tag".into()), ..Default::default() }), ["t1", "t2"]);
+        assert_eq!(uuids(SceneQuery { script: Some("sway*".into()), ..Default::default() }), ["t2"]);
+        assert_eq!(uuids(SceneQuery { texture: Some("wood".into()), ..Default::default() }), ["t1"]);
+        assert_eq!(
+            uuids(SceneQuery { name: Some("tree".into()), material: Some("bark".into()), ..Default::default() }),
+            ["t1"]
+        );
+
+        let hit = &query_document("Main.json", &document, &SceneQuery { kind: Some("PointLight".into()), ..Default::default() })[0];
+        assert_eq!(hit.path, "Level/Lamp");
+    }
+}
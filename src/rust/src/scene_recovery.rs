@@ -0,0 +1,134 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::atomic_write::write_atomic;
+use crate::project_metadata::is_valid_scene_name;
+use crate::scene::SceneDocument;
+use crate::scene_store;
+
+/// Autosave journals live in `<project>/.recovery/<scene>`, one per scene.
+pub const RECOVERY_DIR: &str = ".recovery";
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryInfo {
+    pub scene_name: String,
+    /// When the journal was last written, in seconds since the epoch.
+    pub saved_at: u64,
+    /// When the scene file was last saved, if it exists.
+    pub scene_modified: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveredScene {
+    pub scene_name: String,
+    pub content: String,
+    pub saved_at: u64,
+}
+
+/// Fails for names that aren't plain scene file names, so a journal can't
+/// be read or written outside `RECOVERY_DIR`.
+fn journal_path(project_path: &Path, scene_name: &str) -> Result<PathBuf, String> {
+    if !is_valid_scene_name(scene_name) {
+        return Err(format!("Invalid scene name '{}'", scene_name));
+    }
+    Ok(project_path.join(RECOVERY_DIR).join(scene_name))
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn to_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Stores the editor's current, unsaved state of a scene in its journal.
+pub fn autosave(project_path: &Path, scene_name: &str, content: &str) -> Result<(), String> {
+    let path = journal_path(project_path, scene_name)?;
+    SceneDocument::from_json_str(content)?;
+
+    fs::create_dir_all(project_path.join(RECOVERY_DIR))
+        .map_err(|e| format!("Failed to create recovery directory: {}", e))?;
+    write_atomic(&path, content)
+        .map_err(|e| format!("Failed to write recovery journal: {}", e))
+}
+
+/// Journals that are newer than the scene they belong to, i.e. edits that
+/// were never saved. With `prune`, journals that are not newer are deleted.
+pub fn find_recoverable(project_path: &Path, prune: bool) -> Vec<RecoveryInfo> {
+    let Ok(entries) = fs::read_dir(project_path.join(RECOVERY_DIR)) else {
+        return Vec::new();
+    };
+
+    let mut recoverable = Vec::new();
+    for entry in entries.flatten() {
+        let Some(scene_name) = entry.file_name().to_str().map(|n| n.to_string()) else {
+            continue;
+        };
+        if scene_name.starts_with('.') {
+            continue;
+        }
+        let Some(saved_at) = modified_time(&entry.path()) else {
+            continue;
+        };
+
+        let scene_modified = scene_store::scene_modified(project_path, &scene_name);
+        if scene_modified.is_some_and(|scene_modified| scene_modified >= saved_at) {
+            if prune {
+                let _ = fs::remove_file(entry.path());
+            }
+            continue;
+        }
+
+        recoverable.push(RecoveryInfo {
+            scene_name,
+            saved_at: to_secs(saved_at),
+            scene_modified: scene_modified.map(to_secs),
+        });
+    }
+
+    recoverable.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+    recoverable
+}
+
+pub fn read_journal(project_path: &Path, scene_name: &str) -> Result<RecoveredScene, String> {
+    let path = journal_path(project_path, scene_name)?;
+    let content = fs::read_to_string(&path)
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                format!("No recovery data for {}", scene_name)
+            } else {
+                format!("Failed to read recovery journal: {}", e)
+            }
+        })?;
+
+    Ok(RecoveredScene {
+        scene_name: scene_name.to_string(),
+        content,
+        saved_at: modified_time(&path).map(to_secs).unwrap_or(0),
+    })
+}
+
+/// Removes a scene's journal, e.g. after a clean save. A missing journal is
+/// not an error.
+pub fn clear(project_path: &Path, scene_name: &str) -> Result<(), String> {
+    match fs::remove_file(journal_path(project_path, scene_name)?) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to clear recovery journal: {}", e)),
+    }
+}
+
+/// Moves a renamed scene's journal along with it.
+pub fn rename_journal(project_path: &Path, old_name: &str, new_name: &str) {
+    let (Ok(from), Ok(to)) = (journal_path(project_path, old_name), journal_path(project_path, new_name)) else {
+        return;
+    };
+    if from.exists() {
+        let _ = fs::rename(&from, to);
+    }
+}
@@ -0,0 +1,294 @@
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::scene::{Object3D, Resource, SceneDocument};
+
+/// Counts that only need the outline of a scene: the object tree and the
+/// length of each table. Cheap enough to compute for every scene in a
+/// listing.
+#[derive(Debug, Default, Serialize)]
+pub struct SceneSummary {
+    /// Objects below the scene root.
+    pub objects: usize,
+    pub lights: usize,
+    pub cameras: usize,
+    pub geometries: usize,
+    pub materials: usize,
+    pub textures: usize,
+    pub images: usize,
+    pub scripts: usize,
+    /// Depth of the deepest object; children of the root are at depth 1.
+    pub max_depth: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SceneStats {
+    #[serde(flatten)]
+    pub summary: SceneSummary,
+    pub objects_by_type: BTreeMap<String, usize>,
+    /// Vertices and triangles drawn, counted per object using a geometry.
+    /// Only geometries stored as BufferGeometry data are measured; those
+    /// generated from parameters (BoxGeometry, ...) count as zero.
+    pub vertices: u64,
+    pub triangles: u64,
+    /// Decoded size of the images embedded in the scene. Images referenced
+    /// by URL count as zero.
+    pub image_bytes: u64,
+}
+
+/// Just enough of a scene file to count its outline. The resource tables
+/// and geometry data are skipped without being built.
+#[derive(Deserialize)]
+struct OutlineDocument {
+    scene: OutlineJson,
+    #[serde(default)]
+    scripts: BTreeMap<String, Vec<IgnoredAny>>,
+}
+
+#[derive(Deserialize)]
+struct OutlineJson {
+    #[serde(default)]
+    geometries: Vec<IgnoredAny>,
+    #[serde(default)]
+    materials: Vec<IgnoredAny>,
+    #[serde(default)]
+    textures: Vec<IgnoredAny>,
+    #[serde(default)]
+    images: Vec<IgnoredAny>,
+    object: OutlineObject,
+}
+
+#[derive(Deserialize)]
+struct OutlineObject {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    children: Vec<OutlineObject>,
+}
+
+fn is_light(kind: &str) -> bool {
+    kind.ends_with("Light") || kind == "LightProbe"
+}
+
+fn is_camera(kind: &str) -> bool {
+    kind.ends_with("Camera")
+}
+
+impl SceneSummary {
+    fn count_object(&mut self, kind: &str, depth: usize) {
+        self.objects += 1;
+        self.max_depth = self.max_depth.max(depth);
+        if is_light(kind) {
+            self.lights += 1;
+        } else if is_camera(kind) {
+            self.cameras += 1;
+        }
+    }
+}
+
+/// Summarizes scene content without looking at geometry or image data.
+pub fn summarize_scene(content: &str) -> Result<SceneSummary, String> {
+    fn walk(object: &OutlineObject, depth: usize, summary: &mut SceneSummary) {
+        for child in &object.children {
+            summary.count_object(&child.kind, depth);
+            walk(child, depth + 1, summary);
+        }
+    }
+
+    let document: OutlineDocument = serde_json::from_str(content)
+        .map_err(|e| format!("Invalid scene: {}", e))?;
+
+    let mut summary = SceneSummary {
+        geometries: document.scene.geometries.len(),
+        materials: document.scene.materials.len(),
+        textures: document.scene.textures.len(),
+        images: document.scene.images.len(),
+        scripts: document.scripts.values().map(Vec::len).sum(),
+        ..Default::default()
+    };
+    walk(&document.scene.object, 1, &mut summary);
+    Ok(summary)
+}
+
+/// Full statistics of a scene. Only the scene tree is counted; the camera
+/// stored alongside it is the editor's viewpoint.
+pub fn scene_stats(content: &str) -> Result<SceneStats, String> {
+    fn walk(object: &Object3D, depth: usize, geometry_sizes: &HashMap<&str, (u64, u64)>, stats: &mut SceneStats) {
+        for child in &object.children {
+            stats.summary.count_object(&child.kind, depth);
+            *stats.objects_by_type.entry(child.kind.clone()).or_default() += 1;
+
+            let size = child.geometry.as_deref().and_then(|uuid| geometry_sizes.get(uuid));
+            if let Some((vertices, triangles)) = size {
+                let instances = child.extra.get("count").and_then(Value::as_u64).unwrap_or(1);
+                stats.vertices += vertices * instances;
+                // Points and lines share the geometry format but draw no
+                // triangles.
+                if child.kind.ends_with("Mesh") {
+                    stats.triangles += triangles * instances;
+                }
+            }
+
+            walk(child, depth + 1, geometry_sizes, stats);
+        }
+    }
+
+    let document = SceneDocument::from_json_str(content)?;
+    let scene = &document.scene;
+
+    let geometry_sizes: HashMap<&str, (u64, u64)> = scene.geometries.iter()
+        .map(|geometry| (geometry.uuid.as_str(), geometry_size(geometry)))
+        .collect();
+
+    let mut stats = SceneStats {
+        summary: SceneSummary {
+            geometries: scene.geometries.len(),
+            materials: scene.materials.len(),
+            textures: scene.textures.len(),
+            images: scene.images.len(),
+            scripts: document.scripts.values().map(Vec::len).sum(),
+            ..Default::default()
+        },
+        image_bytes: scene.images.iter().map(image_bytes).sum(),
+        ..Default::default()
+    };
+    walk(&scene.object, 1, &geometry_sizes, &mut stats);
+    Ok(stats)
+}
+
+/// Vertex and triangle count of a BufferGeometry from its `data`.
+fn geometry_size(geometry: &Resource) -> (u64, u64) {
+    let Some(data) = geometry.extra.get("data").and_then(Value::as_object) else {
+        return (0, 0);
+    };
+
+    let array_len = |attribute: Option<&Value>| -> Option<u64> {
+        attribute?.get("array")?.as_array().map(|array| array.len() as u64)
+    };
+
+    let position = data.get("attributes").and_then(|attributes| attributes.get("position"));
+    let item_size = position
+        .and_then(|position| position.get("itemSize"))
+        .and_then(Value::as_u64)
+        .filter(|size| *size > 0)
+        .unwrap_or(3);
+    let vertices = array_len(position).unwrap_or(0) / item_size;
+
+    let triangles = match array_len(data.get("index")) {
+        Some(indices) => indices / 3,
+        None => vertices / 3,
+    };
+
+    (vertices, triangles)
+}
+
+/// Decoded size of an embedded image: data URLs (one, or six for a cube
+/// map), or raw pixel data as written for DataTextures.
+fn image_bytes(image: &Resource) -> u64 {
+    fn source_bytes(source: &Value) -> u64 {
+        match source {
+            Value::String(url) => data_url_bytes(url),
+            Value::Object(data) => raw_bytes(data),
+            _ => 0,
+        }
+    }
+
+    match image.extra.get("url") {
+        Some(Value::Array(sources)) => sources.iter().map(source_bytes).sum(),
+        Some(source) => source_bytes(source),
+        None => 0,
+    }
+}
+
+fn data_url_bytes(url: &str) -> u64 {
+    let Some((header, payload)) = url.strip_prefix("data:").and_then(|rest| rest.split_once(',')) else {
+        return 0;
+    };
+
+    if header.ends_with(";base64") {
+        let payload = payload.trim_end_matches('=');
+        payload.len() as u64 * 3 / 4
+    } else {
+        payload.len() as u64
+    }
+}
+
+fn raw_bytes(data: &Map<String, Value>) -> u64 {
+    let len = data.get("data").and_then(Value::as_array).map_or(0, |array| array.len() as u64);
+    let element_size = match data.get("type").and_then(Value::as_str) {
+        Some("Uint16Array" | "Int16Array") => 2,
+        Some("Uint32Array" | "Int32Array" | "Float32Array") => 4,
+        Some("Float64Array") => 8,
+        _ => 1,
+    };
+    len * element_size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENE: &str = r#"{
+        "camera": { "object": { "type": "PerspectiveCamera" } },
+        "scene": {
+            "geometries": [
+                { "uuid": "indexed", "type": "BufferGeometry", "data": {
+                    "attributes": { "position": { "itemSize": 3, "array": [0, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0] } },
+                    "index": { "array": [0, 1, 2, 0, 2, 3] }
+                } },
+                { "uuid": "plain", "type": "BufferGeometry", "data": {
+                    "attributes": { "position": { "itemSize": 3, "array": [0, 0, 0, 1, 0, 0, 1, 1, 0] } }
+                } },
+                { "uuid": "box", "type": "BoxGeometry", "width": 1 }
+            ],
+            "images": [
+                { "uuid": "a", "url": "data:image/png;base64,AAAAAA==" },
+                { "uuid": "b", "url": { "data": [0, 0, 0, 0], "type": "Float32Array" } },
+                { "uuid": "c", "url": "assets/textures/wood.png" }
+            ],
+            "object": { "type": "Scene", "children": [
+                { "type": "Mesh", "geometry": "indexed" },
+                { "type": "InstancedMesh", "geometry": "plain", "count": 10 },
+                { "type": "Points", "geometry": "plain" },
+                { "type": "Group", "children": [
+                    { "type": "Mesh", "geometry": "box" },
+                    { "type": "DirectionalLight" }
+                ] }
+            ] }
+        }
+    }"#;
+
+    #[test]
+    fn counts_indexed_and_non_indexed_triangles() {
+        let stats = scene_stats(SCENE).unwrap();
+        // 4 + 3 * 10 + 3, and the generated box counts as zero.
+        assert_eq!(stats.vertices, 37);
+        // 2 indexed + 1 * 10 instances; points draw none.
+        assert_eq!(stats.triangles, 12);
+        assert_eq!(stats.objects_by_type.get("Mesh"), Some(&2));
+    }
+
+    #[test]
+    fn sizes_embedded_images() {
+        assert_eq!(data_url_bytes("data:image/png;base64,AAAA"), 3);
+        assert_eq!(data_url_bytes("data:image/png;base64,AAA="), 2);
+        assert_eq!(data_url_bytes("data:image/png;base64,AA=="), 1);
+        assert_eq!(data_url_bytes("data:text/plain,hello"), 5);
+        assert_eq!(data_url_bytes("assets/textures/wood.png"), 0);
+
+        // 4 from base64, 16 of floats, nothing for the URL.
+        assert_eq!(scene_stats(SCENE).unwrap().image_bytes, 20);
+    }
+
+    #[test]
+    fn summarizes_the_outline() {
+        let summary = summarize_scene(SCENE).unwrap();
+        assert_eq!(summary.objects, 6);
+        assert_eq!(summary.lights, 1);
+        assert_eq!(summary.geometries, 3);
+        assert_eq!(summary.images, 3);
+        assert_eq!(summary.max_depth, 2);
+    }
+}
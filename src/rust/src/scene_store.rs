@@ -0,0 +1,617 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::atomic_write::write_atomic;
+use crate::project_metadata::{is_valid_scene_name, ProjectMetadata, SceneEncoding, SceneStorage};
+use crate::project_metadata_store::ProjectMetadataStore;
+use crate::project_watcher::note_own_write;
+use crate::scene::SceneDocument;
+use crate::scene_codec::{decode_scene, encode_scene, is_binary_scene, BINARY_SCENE_EXTENSION};
+use crate::scene_recovery;
+use crate::undo_history;
+
+/// Backups live in `scenes/.backup/<scene>.<n>`, `1` being the most recent.
+pub const BACKUP_DIR: &str = ".backup";
+
+/// `BACKUP_DIR` relative to the project root.
+pub const BACKUP_PATH: &str = "scenes/.backup";
+
+/// Number of backups kept per scene.
+pub const MAX_BACKUPS: usize = 5;
+
+/// Split scenes are stored in `scenes/<name>.scene/`.
+pub const SPLIT_SCENE_EXTENSION: &str = "scene";
+
+/// Files of a split scene. `scene.json` holds everything that isn't split
+/// out, with the scene's children replaced by the names of their files in
+/// `objects/`.
+const SPLIT_MANIFEST: &str = "scene.json";
+const SPLIT_OBJECTS_DIR: &str = "objects";
+const SPLIT_TABLES: &[&str] = &["geometries", "materials", "textures", "images"];
+
+#[derive(Debug, Serialize)]
+pub struct SceneBackup {
+    pub index: usize,
+    pub modified: u64,
+    pub size: u64,
+}
+
+/// The on-disk forms a scene named `Main.json` can take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SceneLayout {
+    /// `scenes/Main.json`
+    Json,
+    /// `scenes/Main.cbor`
+    Binary,
+    /// `scenes/Main.scene/`
+    Split,
+}
+
+impl SceneLayout {
+    const ALL: [SceneLayout; 3] = [SceneLayout::Split, SceneLayout::Binary, SceneLayout::Json];
+
+    fn new(storage: SceneStorage, encoding: SceneEncoding) -> Self {
+        match (storage, encoding) {
+            (SceneStorage::Split, _) => SceneLayout::Split,
+            (SceneStorage::File, SceneEncoding::Cbor) => SceneLayout::Binary,
+            (SceneStorage::File, SceneEncoding::Json) => SceneLayout::Json,
+        }
+    }
+
+    fn of(metadata: &ProjectMetadata) -> Self {
+        Self::new(metadata.scene_storage, metadata.scene_encoding)
+    }
+
+    fn path(self, project_path: &Path, scene_name: &str) -> PathBuf {
+        let stem = scene_name.strip_suffix(".json").unwrap_or(scene_name);
+        match self {
+            SceneLayout::Json => scenes_dir(project_path).join(scene_name),
+            SceneLayout::Binary => scenes_dir(project_path).join(format!("{}.{}", stem, BINARY_SCENE_EXTENSION)),
+            SceneLayout::Split => scenes_dir(project_path).join(format!("{}.{}", stem, SPLIT_SCENE_EXTENSION)),
+        }
+    }
+
+    /// The file whose modification time is the scene's.
+    fn saved_file(self, project_path: &Path, scene_name: &str) -> PathBuf {
+        match self {
+            SceneLayout::Split => self.path(project_path, scene_name).join(SPLIT_MANIFEST),
+            _ => self.path(project_path, scene_name),
+        }
+    }
+}
+
+fn scenes_dir(project_path: &Path) -> PathBuf {
+    project_path.join("scenes")
+}
+
+fn backup_path(project_path: &Path, scene_name: &str, index: usize) -> PathBuf {
+    scenes_dir(project_path)
+        .join(BACKUP_DIR)
+        .join(format!("{}.{}", scene_name, index))
+}
+
+/// The layout a project is configured for. Scenes are read in any layout,
+/// but written in this one. A project without project.json uses plain JSON;
+/// an unreadable one is an error, as guessing could drop a split or binary
+/// scene for a JSON one.
+fn configured_layout(metadata_store: &ProjectMetadataStore, project_path: &Path) -> Result<SceneLayout, String> {
+    let layout = metadata_store.load_optional(project_path)?
+        .map_or(SceneLayout::Json, |metadata| SceneLayout::of(&metadata));
+    Ok(layout)
+}
+
+/// The layout a scene is currently stored in, if it exists.
+fn stored_layout(project_path: &Path, scene_name: &str) -> Option<SceneLayout> {
+    SceneLayout::ALL.into_iter()
+        .find(|layout| layout.saved_file(project_path, scene_name).is_file())
+}
+
+pub fn scene_exists(project_path: &Path, scene_name: &str) -> bool {
+    stored_layout(project_path, scene_name).is_some()
+}
+
+/// When the scene was last saved, in whatever layout.
+pub fn scene_modified(project_path: &Path, scene_name: &str) -> Option<SystemTime> {
+    let layout = stored_layout(project_path, scene_name)?;
+    fs::metadata(layout.saved_file(project_path, scene_name))
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+/// Names of the project's scenes, e.g. `Main.json`, whatever their layout.
+pub fn list_scene_names(project_path: &Path) -> BTreeSet<String> {
+    let Ok(entries) = fs::read_dir(scenes_dir(project_path)) else {
+        return BTreeSet::new();
+    };
+
+    let binary_suffix = format!(".{}", BINARY_SCENE_EXTENSION);
+    let split_suffix = format!(".{}", SPLIT_SCENE_EXTENSION);
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let path = entry.path();
+            if path.join(SPLIT_MANIFEST).is_file() {
+                name.strip_suffix(&split_suffix).map(|stem| format!("{}.json", stem))
+            } else if !path.is_file() {
+                None
+            } else if let Some(stem) = name.strip_suffix(&binary_suffix) {
+                Some(format!("{}.json", stem))
+            } else {
+                Some(name)
+            }
+        })
+        .filter(|name| is_valid_scene_name(name))
+        .collect()
+}
+
+/// Reads a scene as a single JSON document, assembling or decoding it as
+/// needed.
+pub fn read_scene(project_path: &Path, scene_name: &str) -> Result<String, String> {
+    let Some(layout) = stored_layout(project_path, scene_name) else {
+        return Err("File not found".to_string());
+    };
+    let path = layout.path(project_path, scene_name);
+
+    match layout {
+        SceneLayout::Split => read_split(&path),
+        SceneLayout::Binary => decode_scene(
+            &fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?
+        ),
+        SceneLayout::Json => fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read file: {}", e)),
+    }
+}
+
+/// Writes a scene file. The previous version is rotated into the backups
+/// first and the new content replaces the file atomically. Once saved, the
+/// scene's autosave journal is no longer needed and is cleared.
+///
+/// Scene files never hold the editor's undo history (see `undo_history`),
+/// so a `history` in `content` is left out.
+pub fn write_scene(metadata_store: &ProjectMetadataStore, project_path: &Path, scene_name: &str, content: &str) -> Result<(), String> {
+    let layout = configured_layout(metadata_store, project_path)?;
+    write_scene_in(project_path, scene_name, content, layout)
+}
+
+/// Like `write_scene`, for callers that already hold the project's
+/// metadata, e.g. inside `ProjectMetadataStore::update`.
+pub fn write_scene_for(metadata: &ProjectMetadata, project_path: &Path, scene_name: &str, content: &str) -> Result<(), String> {
+    write_scene_in(project_path, scene_name, content, SceneLayout::of(metadata))
+}
+
+fn write_scene_in(project_path: &Path, scene_name: &str, content: &str, layout: SceneLayout) -> Result<(), String> {
+    if !is_valid_scene_name(scene_name) {
+        return Err(format!("Invalid scene name '{}'", scene_name));
+    }
+    let (content, _) = undo_history::detach(content)?;
+    
+    let scenes_dir = scenes_dir(project_path);
+    fs::create_dir_all(&scenes_dir)
+        .map_err(|e| format!("Failed to create scenes directory: {}", e))?;
+
+    if let Some(current_layout) = stored_layout(project_path, scene_name) {
+        // Single-file scenes are backed up as stored, JSON or binary; a split
+        // scene is assembled into one file.
+        let current = match current_layout {
+            SceneLayout::Split => read_scene(project_path, scene_name)?.into_bytes(),
+            _ => fs::read(current_layout.path(project_path, scene_name))
+                .map_err(|e| format!("Failed to read file: {}", e))?,
+        };
+        rotate_backups(project_path, scene_name)?;
+        write_atomic(&backup_path(project_path, scene_name, 1), current)
+            .map_err(|e| format!("Failed to back up scene: {}", e))?;
+    }
+
+    store_scene(project_path, scene_name, &content, layout)?;
+
+    scene_recovery::clear(project_path, scene_name)
+}
+
+/// Writes a scene in the given layout and removes it in the others.
+fn store_scene(project_path: &Path, scene_name: &str, content: &str, layout: SceneLayout) -> Result<(), String> {
+    // Split scenes are swapped in as a whole directory.
+    for layout in SceneLayout::ALL {
+        note_own_write(&layout.path(project_path, scene_name));
+    }
+
+    let path = layout.path(project_path, scene_name);
+    match layout {
+        SceneLayout::Json => write_atomic(&path, content)
+            .map_err(|e| format!("Failed to write file: {}", e))?,
+        SceneLayout::Binary => write_atomic(&path, encode_scene(content)?)
+            .map_err(|e| format!("Failed to write file: {}", e))?,
+        SceneLayout::Split => write_split(&path, content)?,
+    }
+
+    for other in SceneLayout::ALL.into_iter().filter(|other| *other != layout) {
+        remove_path(&other.path(project_path, scene_name))
+            .map_err(|e| format!("Failed to remove old scene file: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Rewrites every scene of a project in the layout for the given settings.
+pub fn convert_scenes(project_path: &Path, storage: SceneStorage, encoding: SceneEncoding) -> Result<(), String> {
+    let layout = SceneLayout::new(storage, encoding);
+    for scene_name in list_scene_names(project_path) {
+        if stored_layout(project_path, &scene_name) == Some(layout) {
+            continue;
+        }
+        let content = read_scene(project_path, &scene_name)?;
+        store_scene(project_path, &scene_name, &content, layout)?;
+    }
+    Ok(())
+}
+
+pub fn delete_scene(project_path: &Path, scene_name: &str) -> Result<(), String> {
+    for layout in SceneLayout::ALL {
+        let path = layout.path(project_path, scene_name);
+        note_own_write(&path);
+        remove_path(&path)
+            .map_err(|e| format!("Failed to delete scene file: {}", e))?;
+    }
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else if path.is_file() {
+        fs::remove_file(path)
+    } else {
+        Ok(())
+    }
+}
+
+/// Renames a scene in whichever layout it is stored, along with its
+/// backups.
+pub fn rename_scene(project_path: &Path, old_name: &str, new_name: &str) -> Result<(), String> {
+    let layout = stored_layout(project_path, old_name)
+        .ok_or_else(|| "Scene file not found".to_string())?;
+
+    let (from, to) = (layout.path(project_path, old_name), layout.path(project_path, new_name));
+    note_own_write(&from);
+    note_own_write(&to);
+    fs::rename(&from, &to)
+        .map_err(|e| format!("Failed to rename scene file: {}", e))?;
+
+    rename_backups(project_path, old_name, new_name);
+    Ok(())
+}
+
+/// Splits a scene into `dir`. The new directory is built next to the old
+/// one and swapped in, so a failed write leaves the previous version.
+fn write_split(dir: &Path, content: &str) -> Result<(), String> {
+    let mut document: Value = serde_json::from_str(content)
+        .map_err(|e| format!("Invalid scene: {}", e))?;
+
+    let file_name = dir.file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| "Invalid scene name".to_string())?;
+    let staging = dir.with_file_name(format!(".{}.tmp-{}", file_name, std::process::id()));
+    let previous = dir.with_file_name(format!(".{}.old-{}", file_name, std::process::id()));
+    let _ = fs::remove_dir_all(&staging);
+
+    let write_json = |path: PathBuf, value: &Value| -> Result<(), String> {
+        let content = serde_json::to_string_pretty(value)
+            .map_err(|e| format!("Failed to serialize scene: {}", e))?;
+        fs::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    };
+
+    let result = (|| {
+        fs::create_dir_all(staging.join(SPLIT_OBJECTS_DIR))
+            .map_err(|e| format!("Failed to create split scene directory: {}", e))?;
+
+        if let Some(scene) = document.get_mut("scene").and_then(Value::as_object_mut) {
+            for table in SPLIT_TABLES {
+                if let Some(entries) = scene.remove(*table) {
+                    write_json(staging.join(format!("{}.json", table)), &entries)?;
+                }
+            }
+
+            if let Some(object) = scene.get_mut("object").and_then(Value::as_object_mut) {
+                let children = match object.remove("children") {
+                    Some(Value::Array(children)) => children,
+                    _ => Vec::new(),
+                };
+
+                let mut used = HashSet::new();
+                let mut names = Vec::new();
+                for (index, child) in children.iter().enumerate() {
+                    let name = object_file_stem(child, index, &mut used);
+                    write_json(staging.join(SPLIT_OBJECTS_DIR).join(format!("{}.json", name)), child)?;
+                    names.push(Value::String(name));
+                }
+                object.insert("children".to_string(), Value::Array(names));
+            }
+        }
+
+        write_json(staging.join(SPLIT_MANIFEST), &document)
+    })();
+
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    if dir.exists() {
+        fs::rename(dir, &previous)
+            .map_err(|e| format!("Failed to replace split scene: {}", e))?;
+    }
+    if let Err(e) = fs::rename(&staging, dir) {
+        let _ = fs::rename(&previous, dir);
+        let _ = fs::remove_dir_all(&staging);
+        return Err(format!("Failed to replace split scene: {}", e));
+    }
+    let _ = fs::remove_dir_all(&previous);
+
+    Ok(())
+}
+
+/// File name of a top-level object: its uuid, or its position if it has no
+/// usable one.
+fn object_file_stem(object: &Value, index: usize, used: &mut HashSet<String>) -> String {
+    let uuid = object.get("uuid").and_then(Value::as_str).unwrap_or_default();
+    let usable = !uuid.is_empty()
+        && uuid.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    let base = if usable { uuid.to_string() } else { format!("object-{}", index) };
+    let mut name = base.clone();
+    let mut suffix = 1;
+    while !used.insert(name.clone()) {
+        suffix += 1;
+        name = format!("{}-{}", base, suffix);
+    }
+    name
+}
+
+/// Reassembles a split scene into a single document.
+fn read_split(dir: &Path) -> Result<String, String> {
+    let read_json = |path: PathBuf| -> Result<Value, String> {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid scene: {}: {}", path.display(), e))
+    };
+
+    let mut document = read_json(dir.join(SPLIT_MANIFEST))?;
+
+    if let Some(scene) = document.get_mut("scene").and_then(Value::as_object_mut) {
+        let mut tables = Map::new();
+        for table in SPLIT_TABLES {
+            let path = dir.join(format!("{}.json", table));
+            if path.is_file() {
+                tables.insert(table.to_string(), read_json(path)?);
+            }
+        }
+        scene.extend(tables);
+
+        if let Some(object) = scene.get_mut("object").and_then(Value::as_object_mut) {
+            if let Some(Value::Array(names)) = object.get("children") {
+                let children = names.iter()
+                    .map(|name| {
+                        let name = name.as_str()
+                            .filter(|n| !n.is_empty() && !n.contains(['/', '\\', '.']))
+                            .ok_or_else(|| format!("Invalid scene: {}: bad child entry {}", dir.display(), name))?;
+                        read_json(dir.join(SPLIT_OBJECTS_DIR).join(format!("{}.json", name)))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                if children.is_empty() {
+                    object.remove("children");
+                } else {
+                    object.insert("children".to_string(), Value::Array(children));
+                }
+            }
+        }
+    }
+
+    serde_json::to_string_pretty(&document)
+        .map_err(|e| format!("Failed to serialize scene: {}", e))
+}
+
+/// Shifts `<scene>.1 ..` up by one, dropping the oldest, so that slot 1 is
+/// free.
+fn rotate_backups(project_path: &Path, scene_name: &str) -> Result<(), String> {
+    fs::create_dir_all(scenes_dir(project_path).join(BACKUP_DIR))
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    let _ = fs::remove_file(backup_path(project_path, scene_name, MAX_BACKUPS));
+    for index in (1..MAX_BACKUPS).rev() {
+        let from = backup_path(project_path, scene_name, index);
+        if from.exists() {
+            fs::rename(&from, backup_path(project_path, scene_name, index + 1))
+                .map_err(|e| format!("Failed to rotate scene backups: {}", e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the backups of a scene, most recent first.
+pub fn list_backups(project_path: &Path, scene_name: &str) -> Vec<SceneBackup> {
+    (1..=MAX_BACKUPS)
+        .filter_map(|index| {
+            let metadata = fs::metadata(backup_path(project_path, scene_name, index)).ok()?;
+            let modified = metadata.modified().ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            Some(SceneBackup {
+                index,
+                modified,
+                size: metadata.len(),
+            })
+        })
+        .collect()
+}
+
+/// Replaces a scene with one of its backups. The current version is backed
+/// up in turn, so a restore can itself be undone. The editor's undo steps
+/// don't apply to the restored version and are dropped.
+pub fn restore_backup(metadata_store: &ProjectMetadataStore, project_path: &Path, scene_name: &str, index: usize) -> Result<(), String> {
+    let path = backup_path(project_path, scene_name, index);
+    let bytes = fs::read(&path)
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                format!("Backup {} of {} not found", index, scene_name)
+            } else {
+                format!("Failed to read backup: {}", e)
+            }
+        })?;
+    let content = if is_binary_scene(&bytes) {
+        decode_scene(&bytes)?
+    } else {
+        String::from_utf8(bytes)
+            .map_err(|_| format!("Backup {} of {} is not usable: not UTF-8", index, scene_name))?
+    };
+
+    SceneDocument::from_json_str(&content)
+        .map_err(|e| format!("Backup {} of {} is not usable: {}", index, scene_name, e))?;
+
+    write_scene(metadata_store, project_path, scene_name, &content)?;
+    undo_history::clear(project_path, scene_name)
+}
+
+/// Moves the backups of a renamed scene along with it.
+fn rename_backups(project_path: &Path, old_name: &str, new_name: &str) {
+    for index in 1..=MAX_BACKUPS {
+        let from = backup_path(project_path, old_name, index);
+        let to = backup_path(project_path, new_name, index);
+        if from.exists() {
+            let _ = fs::rename(&from, &to);
+        } else {
+            let _ = fs::remove_file(&to);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENE: &str = r#"{
+        "metadata": { "version": 4.6, "type": "Object", "generator": "Object3D.toJSON" },
+        "camera": { "object": { "uuid": "camera", "type": "PerspectiveCamera" } },
+        "scene": {
+            "geometries": [{ "uuid": "g", "type": "BoxGeometry" }],
+            "materials": [{ "uuid": "m", "type": "MeshStandardMaterial" }],
+            "object": { "uuid": "scene", "type": "Scene", "children": [
+                { "uuid": "a", "type": "Mesh", "geometry": "g", "material": "m",
+                  "children": [{ "uuid": "a1", "type": "Group" }] },
+                { "uuid": "../b", "type": "Group" },
+                { "type": "Group", "name": "No uuid" }
+            ] }
+        }
+    }"#;
+
+    fn project(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("scene-store-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn split_metadata() -> ProjectMetadata {
+        let mut metadata = ProjectMetadata::new("Test");
+        metadata.scene_storage = SceneStorage::Split;
+        metadata
+    }
+
+    fn parse(content: &str) -> Value {
+        serde_json::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn split_scene_round_trips() {
+        let project_path = project("split");
+        write_scene_for(&split_metadata(), &project_path, "Main.json", SCENE).unwrap();
+
+        let dir = scenes_dir(&project_path).join("Main.scene");
+        assert!(dir.join(SPLIT_MANIFEST).is_file());
+        assert!(dir.join("geometries.json").is_file());
+        assert!(dir.join("materials.json").is_file());
+        for object in ["a", "object-1", "object-2"] {
+            assert!(dir.join(SPLIT_OBJECTS_DIR).join(format!("{}.json", object)).is_file(), "{}", object);
+        }
+        assert!(!scenes_dir(&project_path).join("Main.json").exists());
+
+        assert_eq!(list_scene_names(&project_path).into_iter().collect::<Vec<_>>(), ["Main.json"]);
+        assert_eq!(parse(&read_scene(&project_path, "Main.json").unwrap()), parse(SCENE));
+
+        let _ = fs::remove_dir_all(&project_path);
+    }
+
+    #[test]
+    fn overwriting_a_split_scene_keeps_a_backup() {
+        let project_path = project("split-backup");
+        let metadata = split_metadata();
+        write_scene_for(&metadata, &project_path, "Main.json", SCENE).unwrap();
+
+        let mut changed = parse(SCENE);
+        changed["scene"]["object"]["children"].as_array_mut().unwrap().truncate(1);
+        let changed = changed.to_string();
+        write_scene_for(&metadata, &project_path, "Main.json", &changed).unwrap();
+
+        let objects = fs::read_dir(scenes_dir(&project_path).join("Main.scene").join(SPLIT_OBJECTS_DIR)).unwrap().count();
+        assert_eq!(objects, 1);
+        assert_eq!(parse(&read_scene(&project_path, "Main.json").unwrap()), parse(&changed));
+
+        let backup = fs::read_to_string(backup_path(&project_path, "Main.json", 1)).unwrap();
+        assert_eq!(parse(&backup), parse(SCENE));
+
+        let _ = fs::remove_dir_all(&project_path);
+    }
+
+    #[test]
+    fn backs_up_binary_scenes_as_stored() {
+        let project_path = project("binary-backup");
+        let mut metadata = ProjectMetadata::new("Test");
+        metadata.scene_encoding = SceneEncoding::Cbor;
+        write_scene_for(&metadata, &project_path, "Main.json", SCENE).unwrap();
+        let stored = fs::read(scenes_dir(&project_path).join("Main.cbor")).unwrap();
+
+        write_scene_for(&metadata, &project_path, "Main.json", r#"{ "scene": {} }"#).unwrap();
+        assert_eq!(fs::read(backup_path(&project_path, "Main.json", 1)).unwrap(), stored);
+
+        restore_backup(&ProjectMetadataStore::new(), &project_path, "Main.json", 1).unwrap();
+        assert_eq!(parse(&read_scene(&project_path, "Main.json").unwrap()), parse(SCENE));
+
+        let _ = fs::remove_dir_all(&project_path);
+    }
+
+    #[test]
+    fn refuses_scene_names_outside_scenes() {
+        let project_path = project("names");
+        for name in ["../../Escaped.json", "../Escaped.json", "nested/Main.json", "Main.txt"] {
+            let result = write_scene_for(&ProjectMetadata::new("Test"), &project_path, name, SCENE);
+            assert_eq!(result, Err(format!("Invalid scene name '{}'", name)));
+        }
+        assert!(!project_path.join("Escaped.json").exists());
+
+        let _ = fs::remove_dir_all(&project_path);
+    }
+
+    #[test]
+    fn converts_between_layouts() {
+        let project_path = project("convert");
+        write_scene_for(&ProjectMetadata::new("Test"), &project_path, "Main.json", SCENE).unwrap();
+
+        convert_scenes(&project_path, SceneStorage::Split, SceneEncoding::Json).unwrap();
+        assert_eq!(stored_layout(&project_path, "Main.json"), Some(SceneLayout::Split));
+        assert!(!scenes_dir(&project_path).join("Main.json").exists());
+
+        convert_scenes(&project_path, SceneStorage::File, SceneEncoding::Cbor).unwrap();
+        assert_eq!(stored_layout(&project_path, "Main.json"), Some(SceneLayout::Binary));
+        assert!(!scenes_dir(&project_path).join("Main.scene").exists());
+        assert_eq!(parse(&read_scene(&project_path, "Main.json").unwrap()), parse(SCENE));
+
+        let _ = fs::remove_dir_all(&project_path);
+    }
+}
@@ -0,0 +1,159 @@
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::atomic_write::write_atomic;
+
+/// Per-project cache of data that is derived from editing and never belongs
+/// in version control.
+pub const CACHE_DIR: &str = ".cache";
+
+/// The editor's undo stack of a scene is kept in
+/// `<project>/.cache/history/<scene>` instead of the scene file.
+const UNDO_HISTORY_DIR: &str = "history";
+
+/// Size of an undo history sidecar. Beyond it, the redos furthest away and
+/// then the oldest undos are dropped.
+pub const MAX_UNDO_HISTORY_BYTES: usize = 16 * 1024 * 1024;
+
+fn sidecar_path(project_path: &Path, scene_name: &str) -> PathBuf {
+    project_path.join(CACHE_DIR).join(UNDO_HISTORY_DIR).join(scene_name)
+}
+
+/// Whether a document has a top-level `history`, found without building the
+/// rest of it.
+#[derive(Deserialize)]
+struct HistoryKey {
+    #[serde(default, deserialize_with = "present")]
+    history: bool,
+}
+
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    IgnoredAny::deserialize(deserializer).map(|_| true)
+}
+
+/// Splits the `history` of the editor off scene content. Content without
+/// one is returned as is, without being reformatted.
+pub fn detach(content: &str) -> Result<(Cow<'_, str>, Option<Value>), String> {
+    let key: HistoryKey = serde_json::from_str(content)
+        .map_err(|e| format!("Invalid scene: {}", e))?;
+    if !key.history {
+        return Ok((Cow::Borrowed(content), None));
+    }
+
+    let mut document: Value = serde_json::from_str(content)
+        .map_err(|e| format!("Invalid scene: {}", e))?;
+    let Some(history) = document.as_object_mut().and_then(|map| map.remove("history")) else {
+        return Ok((Cow::Borrowed(content), None));
+    };
+
+    let content = serde_json::to_string_pretty(&document)
+        .map_err(|e| format!("Failed to serialize scene: {}", e))?;
+    Ok((Cow::Owned(content), Some(history)))
+}
+
+/// Puts the stored undo history of a scene back into its content. Content
+/// is returned unchanged if there is none.
+pub fn attach(project_path: &Path, scene_name: &str, content: String) -> Result<String, String> {
+    let Some(history) = load(project_path, scene_name) else {
+        return Ok(content);
+    };
+
+    let mut document: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid scene: {}", e))?;
+    let Some(map) = document.as_object_mut() else {
+        return Ok(content);
+    };
+    map.insert("history".to_string(), history);
+
+    serde_json::to_string_pretty(&document)
+        .map_err(|e| format!("Failed to serialize scene: {}", e))
+}
+
+/// A missing or unreadable sidecar just means the scene opens without undo
+/// steps.
+fn load(project_path: &Path, scene_name: &str) -> Option<Value> {
+    fs::read_to_string(sidecar_path(project_path, scene_name))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+pub fn save(project_path: &Path, scene_name: &str, mut history: Value) -> Result<(), String> {
+    let path = sidecar_path(project_path, scene_name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+    }
+
+    trim(&mut history);
+    let content = serde_json::to_string(&history)
+        .map_err(|e| format!("Failed to serialize undo history: {}", e))?;
+    write_atomic(&path, content)
+        .map_err(|e| format!("Failed to write undo history: {}", e))
+}
+
+/// Drops steps until the history fits `MAX_UNDO_HISTORY_BYTES`. Both lists
+/// end with the step closest to the saved state.
+fn trim(history: &mut Value) {
+    let size = |value: &Value| serde_json::to_string(value).map_or(0, |s| s.len());
+
+    let mut total = size(history);
+    for key in ["redos", "undos"] {
+        let Some(steps) = history.get_mut(key).and_then(Value::as_array_mut) else {
+            continue;
+        };
+        let mut dropped = 0;
+        while total > MAX_UNDO_HISTORY_BYTES && dropped < steps.len() {
+            // Each step also takes a separating comma.
+            total = total.saturating_sub(size(&steps[dropped]) + 1);
+            dropped += 1;
+        }
+        steps.drain(..dropped);
+    }
+}
+
+/// Forgets the undo history of a scene, e.g. once it has been replaced by a
+/// backup the steps no longer apply to.
+pub fn clear(project_path: &Path, scene_name: &str) -> Result<(), String> {
+    match fs::remove_file(sidecar_path(project_path, scene_name)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove undo history: {}", e)),
+    }
+}
+
+/// Moves the undo history of a renamed scene along with it.
+pub fn rename(project_path: &Path, old_name: &str, new_name: &str) {
+    let from = sidecar_path(project_path, old_name);
+    if from.exists() {
+        let _ = fs::rename(&from, sidecar_path(project_path, new_name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaves_content_without_history_untouched() {
+        // Only the top-level key counts, not objects or properties named so.
+        let content = r#"{"scene":{"object":{"name":"history","userData":{"history":[1]}}}}"#;
+        let (detached, history) = detach(content).unwrap();
+        assert!(matches!(detached, Cow::Borrowed(_)));
+        assert_eq!(detached, content);
+        assert!(history.is_none());
+    }
+
+    #[test]
+    fn splits_off_the_history() {
+        let content = r#"{"history":{"undos":[{"id":1}],"redos":[]},"scene":{}}"#;
+        let (detached, history) = detach(content).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&detached).unwrap(), serde_json::json!({ "scene": {} }));
+        assert_eq!(history, Some(serde_json::json!({ "undos": [{ "id": 1 }], "redos": [] })));
+
+        assert!(detach("not json").is_err());
+    }
+}